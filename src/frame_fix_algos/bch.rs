@@ -0,0 +1,226 @@
+// Двоичные коды БЧХ (BCH) длины n = 2^m - 1, исправляющие до t ошибок.
+// Порождающий полином - НОК минимальных полиномов элементов a, a^2, .., a^2t,
+// минимальный полином считается как произведение (x - a^j) по циклотомическому
+// классу j, 2j, 4j, .. (mod n) и всегда имеет двоичные коэффициенты.
+// Кодирование систематическое: за k битами данных следуют n - k проверочных бит,
+// первый бит строки - старший коэффициент. Декодирование: синдромы
+// S_j = r(a^j), локатор ошибок по Берлекэмпу-Месси и его корни поиском Ченя.
+
+use crate::frame_fix_algos::block_code::BlockCode;
+use crate::frame_fix_algos::decode_report::{BlockDecodeResult, DecodeStatus};
+use crate::galois::{berlekamp_massey, GaloisField, GfPolynomial};
+use crate::utils::{bits_from_str, bits_to_string};
+
+
+pub struct BchCode {
+    field: GaloisField,
+    binary_field: GaloisField,
+    codeword_bits: usize,
+    data_bits: usize,
+    correctable_errors: usize,
+    generator: GfPolynomial,
+}
+
+impl BchCode {
+    /// Narrow-sense BCH code of length 2^m - 1 over the default primitive polynomial
+    pub fn new(m: u32, correctable_errors: usize) -> Self {
+        Self::with_field(GaloisField::with_default_polynomial(m), correctable_errors)
+    }
+
+    pub fn with_field(field: GaloisField, correctable_errors: usize) -> Self {
+        let codeword_bits = field.order();
+        assert!(correctable_errors > 0, "Code must correct at least one error");
+        assert!(2 * correctable_errors < codeword_bits, "Too many correctable errors for length {codeword_bits}");
+
+        let mut covered = vec![false; codeword_bits];
+        let mut generator = GfPolynomial::one();
+        for power in 1..=2 * correctable_errors {
+            if covered[power] {
+                continue;
+            }
+            let mut minimal_polynomial = GfPolynomial::one();
+            let mut conjugate = power;
+            while !covered[conjugate] {
+                covered[conjugate] = true;
+                let root = field.exp(conjugate as i64);
+                minimal_polynomial = minimal_polynomial.multiply(&GfPolynomial::new(vec![root, 1]), &field);
+                conjugate = conjugate * 2 % codeword_bits;
+            }
+            generator = generator.multiply(&minimal_polynomial, &field);
+        }
+
+        let data_bits = codeword_bits - generator.degree().unwrap();
+        assert!(data_bits > 0, "Generator polynomial leaves no data bits");
+        BchCode {
+            field,
+            binary_field: GaloisField::binary(),
+            codeword_bits,
+            data_bits,
+            correctable_errors,
+            generator,
+        }
+    }
+
+    pub fn codeword_bits(&self) -> usize { self.codeword_bits }
+
+    pub fn data_bits(&self) -> usize { self.data_bits }
+
+    pub fn correctable_errors(&self) -> usize { self.correctable_errors }
+
+    pub fn generator(&self) -> &GfPolynomial { &self.generator }
+
+    pub fn encode(&self, data: &str) -> String {
+        assert_eq!(data.len(), self.data_bits, "BCH({}, {}) encodes {} bits", self.codeword_bits, self.data_bits, self.data_bits);
+        let parity_bits = self.codeword_bits - self.data_bits;
+        let message = GfPolynomial::from_bits(data).shift(parity_bits);
+        let (_, remainder) = message.div_rem(&self.generator, &self.binary_field);
+        format!("{}{}", data, remainder.to_bits(parity_bits))
+    }
+
+    pub fn decode(&self, codeword: &str) -> BlockDecodeResult {
+        assert_eq!(codeword.len(), self.codeword_bits, "BCH codeword must have {} bits", self.codeword_bits);
+        let received = GfPolynomial::from_bits(codeword);
+        let syndromes = (1..=2 * self.correctable_errors)
+            .map(|power| received.evaluate(self.field.exp(power as i64), &self.field))
+            .collect::<Vec<u16>>();
+        if syndromes.iter().all(|&syndrome| syndrome == 0) {
+            return BlockDecodeResult {
+                data: codeword[..self.data_bits].to_string(),
+                corrected_positions: Vec::new(),
+                status: DecodeStatus::Clean,
+            };
+        }
+
+        let error_locator = berlekamp_massey(&syndromes, GfPolynomial::one(), 0, &self.field);
+        let errors_count = error_locator.degree().unwrap_or(0);
+        // Поиск Ченя: ошибка в степени d, если Lambda(a^-d) = 0
+        let error_degrees = (0..self.codeword_bits)
+            .filter(|&degree| error_locator.evaluate(self.field.exp(-(degree as i64)), &self.field) == 0)
+            .collect::<Vec<usize>>();
+        // Локатор степени больше t или с неполным набором корней - ошибок больше t
+        if errors_count == 0 || errors_count > self.correctable_errors || error_degrees.len() != errors_count {
+            return BlockDecodeResult {
+                data: codeword[..self.data_bits].to_string(),
+                corrected_positions: Vec::new(),
+                status: DecodeStatus::Uncorrectable,
+            };
+        }
+
+        let mut bits = bits_from_str(codeword);
+        let mut corrected_positions = error_degrees.iter()
+            .map(|&degree| self.codeword_bits - 1 - degree)
+            .collect::<Vec<usize>>();
+        corrected_positions.sort_unstable();
+        for &position in &corrected_positions {
+            bits[position] ^= 1;
+        }
+        BlockDecodeResult {
+            data: bits_to_string(&bits[..self.data_bits]),
+            corrected_positions,
+            status: DecodeStatus::Corrected,
+        }
+    }
+}
+
+
+impl BlockCode for BchCode {
+    fn codeword_bits(&self) -> usize { self.codeword_bits }
+
+    fn data_bits(&self) -> usize { self.data_bits }
+
+    fn encode(&self, data: &str) -> String { BchCode::encode(self, data) }
+
+    fn decode(&self, codeword: &str) -> BlockDecodeResult { BchCode::decode(self, codeword) }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame_fix_algos::block_code::for_each_combination;
+    use crate::test_utils::flip_bits;
+
+    #[test]
+    fn known_code_parameters() {
+        let bch = BchCode::new(4, 2);
+        assert_eq!((bch.codeword_bits(), bch.data_bits()), (15, 7));
+        // x^8 + x^7 + x^6 + x^4 + 1
+        assert_eq!(bch.generator().to_bits(9), "111010001");
+
+        assert_eq!(BchCode::new(4, 3).generator().to_bits(11), "10100110111");
+        assert_eq!(BchCode::new(6, 2).data_bits(), 51);
+        assert_eq!(BchCode::new(8, 2).data_bits(), 239);
+        assert_eq!(BchCode::new(5, 3).data_bits(), 16);
+    }
+
+    #[test]
+    fn codeword_divisible_by_generator() {
+        let bch = BchCode::new(4, 2);
+        let codeword = bch.encode("1011001");
+        assert_eq!(&codeword[..7], "1011001");
+        let (_, remainder) = GfPolynomial::from_bits(&codeword).div_rem(bch.generator(), &GaloisField::binary());
+        assert!(remainder.is_zero());
+
+        let result = bch.decode(&codeword);
+        assert_eq!(result.status, DecodeStatus::Clean);
+        assert_eq!(result.data, "1011001");
+    }
+
+    #[test]
+    fn all_double_errors_corrected_in_bch_15_7() {
+        let bch = BchCode::new(4, 2);
+        let codeword = bch.encode("1100101");
+        for first in 0..15 {
+            for second in first + 1..15 {
+                let result = bch.decode(&flip_bits(&codeword, &[first, second]));
+                assert_eq!(result.data, "1100101");
+                assert_eq!(result.corrected_positions, vec![first, second]);
+                assert_eq!(result.status, DecodeStatus::Corrected);
+            }
+        }
+    }
+
+    #[test]
+    fn long_codes_corrected() {
+        let bch = BchCode::new(8, 2);
+        let data = "1011001110001111".repeat(15)[..239].to_string();
+        let codeword = bch.encode(&data);
+        let result = bch.decode(&flip_bits(&codeword, &[3, 250]));
+        assert_eq!(result.data, data);
+        assert_eq!(result.corrected_positions, vec![3, 250]);
+
+        let bch = BchCode::new(6, 2);
+        let data = "110100111000101".repeat(4)[..51].to_string();
+        let codeword = bch.encode(&data);
+        assert_eq!(bch.decode(&flip_bits(&codeword, &[62])).data, data);
+    }
+
+    #[test]
+    fn too_many_errors_detected() {
+        // Код (31, 16, t = 3) с четырьмя ошибками
+        let bch = BchCode::new(5, 3);
+        let codeword = bch.encode("1011001110001111");
+        let result = bch.decode(&flip_bits(&codeword, &[0, 5, 17, 30]));
+        assert_eq!(result.status, DecodeStatus::Uncorrectable);
+        assert!(result.corrected_positions.is_empty());
+    }
+
+    #[test]
+    fn beyond_t_patterns_never_corrected_past_t() {
+        // Три и четыре ошибки в коде (15, 7, t = 2): либо отказ, либо переход
+        // в кодовое слово на расстоянии не больше t
+        let bch = BchCode::new(4, 2);
+        let codeword = bch.encode("0110110");
+        for weight in 3..=4 {
+            for_each_combination(15, weight, &mut |positions| {
+                let received = flip_bits(&codeword, positions);
+                let result = bch.decode(&received);
+                if result.status == DecodeStatus::Corrected {
+                    assert!(result.corrected_positions.len() <= 2, "{positions:?}");
+                    let corrected = flip_bits(&received, &result.corrected_positions);
+                    assert_eq!(bch.decode(&corrected).status, DecodeStatus::Clean, "{positions:?}");
+                }
+            });
+        }
+    }
+}
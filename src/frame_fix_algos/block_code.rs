@@ -0,0 +1,445 @@
+// Общий интерфейс блочных кодов и линейный блочный код над GF(2), заданный
+// порождающей матрицей G (k x n) или проверочной матрицей H ((n-k) x n).
+// Кодирование - умножение строки данных на G как она задана. Приведение G к
+// ступенчатому виду даёт информационные позиции (ведущие столбцы) и матрицу T,
+// по которой данные восстанавливаются из кодового слова: d = c[ведущие] * T.
+// Декодирование по таблице синдромов (стандартная расстановка): для каждого
+// синдрома хранится лидер смежного класса - вектор ошибок наименьшего веса.
+// Если таких векторов несколько, ошибка только обнаруживается (как двойная
+// ошибка в SECDED).
+
+use crate::frame_fix_algos::decode_report::{BlockDecodeResult, DecodeStatus};
+use crate::frame_fix_algos::hamming_code;
+use crate::utils::{bits_from_str, bits_to_string};
+
+// Ограничения на размер таблицы синдромов и перебор кодовых слов
+const MAX_PARITY_BITS: usize = 20;
+const MAX_ENUMERATED_DATA_BITS: usize = 24;
+
+
+/// Binary block code mapping k data bits to n codeword bits
+pub trait BlockCode {
+    fn codeword_bits(&self) -> usize;
+
+    fn data_bits(&self) -> usize;
+
+    fn encode(&self, data: &str) -> String;
+
+    fn decode(&self, codeword: &str) -> BlockDecodeResult;
+
+    /// Data bits of a codeword read as they are, without correcting errors. The default
+    /// decodes, which is exact only for codewords; codes override it where they can.
+    fn extract_data(&self, codeword: &str) -> String {
+        self.decode(codeword).data
+    }
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CosetLeader {
+    error_positions: Vec<usize>,
+    /// Another error pattern of the same weight has the same syndrome
+    ambiguous: bool,
+}
+
+
+#[derive(Debug, Clone)]
+pub struct LinearBlockCode {
+    codeword_bits: usize,
+    generator: Vec<Vec<u8>>,
+    /// Pivot column of every row of the reduced generator
+    information_positions: Vec<usize>,
+    /// Maps information bits of a codeword back to data bits
+    data_transform: Vec<Vec<u8>>,
+    parity_check: Vec<Vec<u8>>,
+    syndrome_table: Vec<CosetLeader>,
+}
+
+impl LinearBlockCode {
+    /// Code from the rows of a generator matrix like "1000110"
+    pub fn from_generator(rows: &[&str]) -> Self {
+        Self::from_generator_rows(parse_matrix(rows))
+    }
+
+    /// Code from the rows of a parity-check matrix. Data bits take the columns
+    /// left of the parity columns, so H = [P^T | I] gives G = [I | P].
+    pub fn from_parity_check(rows: &[&str]) -> Self {
+        let mut parity_check = parse_matrix(rows);
+        let codeword_bits = parity_check[0].len();
+        let pivots = row_reduce(&mut parity_check, (0..codeword_bits).rev());
+
+        let mut is_pivot = vec![false; codeword_bits];
+        for &pivot in &pivots {
+            is_pivot[pivot] = true;
+        }
+        let generator = (0..codeword_bits).filter(|&column| !is_pivot[column]).map(|free_column| {
+            let mut row = vec![0; codeword_bits];
+            row[free_column] = 1;
+            for (check, &pivot) in pivots.iter().enumerate() {
+                row[pivot] = parity_check[check][free_column];
+            }
+            row
+        }).collect::<Vec<Vec<u8>>>();
+        assert!(!generator.is_empty(), "Parity-check matrix leaves no data bits");
+        Self::from_generator_rows(generator)
+    }
+
+    /// Hamming code with `parity_bits` check bits, encoding like `hamming_code::encode`
+    pub fn hamming(parity_bits: usize) -> Self {
+        let data_bits = (1 << parity_bits) - 1 - parity_bits;
+        Self::from_generator_rows(unit_rows(data_bits, hamming_code::encode))
+    }
+
+    /// Extended Hamming (SECDED) code, encoding like `hamming_code::encode_extended`
+    pub fn secded(parity_bits: usize) -> Self {
+        let data_bits = (1 << parity_bits) - 1 - parity_bits;
+        Self::from_generator_rows(unit_rows(data_bits, hamming_code::encode_extended))
+    }
+
+    fn from_generator_rows(generator: Vec<Vec<u8>>) -> Self {
+        assert!(!generator.is_empty(), "Generator matrix has no rows");
+        let codeword_bits = generator[0].len();
+        let data_bits = generator.len();
+        assert!(generator.iter().all(|row| row.len() == codeword_bits), "All rows of G must have the same length");
+        assert!(data_bits < codeword_bits, "Code must have parity bits");
+        assert!(codeword_bits - data_bits <= MAX_PARITY_BITS, "Syndrome table is limited to {MAX_PARITY_BITS} parity bits");
+
+        // [G | I] -> [T * G | T], T * G в приведённом ступенчатом виде
+        let mut augmented = generator.iter().enumerate().map(|(index, row)| {
+            let mut augmented_row = row.clone();
+            augmented_row.extend((0..data_bits).map(|column| (column == index) as u8));
+            augmented_row
+        }).collect::<Vec<Vec<u8>>>();
+        let information_positions = row_reduce(&mut augmented, 0..codeword_bits);
+        assert_eq!(information_positions.len(), data_bits, "Rows of the generator matrix are linearly dependent");
+        let data_transform = augmented.iter().map(|row| row[codeword_bits..].to_vec()).collect();
+
+        // Строка H для каждого неведущего столбца q: c[q] + сумма c[p_i] по строкам с единицей в q
+        let mut is_information = vec![false; codeword_bits];
+        for &position in &information_positions {
+            is_information[position] = true;
+        }
+        let parity_check = (0..codeword_bits).filter(|&column| !is_information[column]).map(|column| {
+            let mut row = vec![0; codeword_bits];
+            row[column] = 1;
+            for (reduced_row, &position) in augmented.iter().zip(&information_positions) {
+                row[position] = reduced_row[column];
+            }
+            row
+        }).collect::<Vec<Vec<u8>>>();
+
+        let syndrome_table = build_syndrome_table(&parity_check, codeword_bits);
+        LinearBlockCode { codeword_bits, generator, information_positions, data_transform, parity_check, syndrome_table }
+    }
+
+    /// Same code with the generator in reduced row echelon form, so every data
+    /// bit is copied to its information position
+    pub fn systematic(&self) -> Self {
+        let mut reduced = self.generator.clone();
+        row_reduce(&mut reduced, 0..self.codeword_bits);
+        Self::from_generator_rows(reduced)
+    }
+
+    pub fn generator_matrix(&self) -> Vec<String> {
+        self.generator.iter().map(|row| bits_to_string(row)).collect()
+    }
+
+    pub fn parity_check_matrix(&self) -> Vec<String> {
+        self.parity_check.iter().map(|row| bits_to_string(row)).collect()
+    }
+
+    /// Codeword positions which determine the data bits
+    pub fn information_positions(&self) -> &[usize] { &self.information_positions }
+
+    pub fn syndrome(&self, codeword: &str) -> String {
+        assert_eq!(codeword.len(), self.codeword_bits, "Codeword must have {} bits", self.codeword_bits);
+        let bits = bits_from_str(codeword);
+        self.parity_check.iter()
+            .map(|row| if dot(row, &bits) == 1 { '1' } else { '0' })
+            .collect()
+    }
+
+    /// Smallest weight of a nonzero codeword, found by enumerating all codewords
+    pub fn minimum_distance(&self) -> usize {
+        let data_bits = self.generator.len();
+        assert!(data_bits <= MAX_ENUMERATED_DATA_BITS, "Too many codewords to enumerate");
+        // Код Грея: соседние слова данных отличаются одним битом, кодовые слова - одной строкой G
+        let mut codeword = vec![0u8; self.codeword_bits];
+        let mut minimum = self.codeword_bits;
+        for step in 1u64..1 << data_bits {
+            let row = &self.generator[step.trailing_zeros() as usize];
+            for (bit, &row_bit) in codeword.iter_mut().zip(row) {
+                *bit ^= row_bit;
+            }
+            minimum = minimum.min(codeword.iter().filter(|&&bit| bit == 1).count());
+        }
+        minimum
+    }
+
+    fn encode_bits(&self, data: &[u8]) -> Vec<u8> {
+        let mut codeword = vec![0u8; self.codeword_bits];
+        for (row, _) in self.generator.iter().zip(data).filter(|(_, &bit)| bit == 1) {
+            for (bit, &row_bit) in codeword.iter_mut().zip(row) {
+                *bit ^= row_bit;
+            }
+        }
+        codeword
+    }
+
+    /// Data bits of a codeword from its information bits
+    fn transform(&self, information_bits: &[u8]) -> Vec<u8> {
+        let mut data = vec![0u8; self.generator.len()];
+        for (row, _) in self.data_transform.iter().zip(information_bits).filter(|(_, &bit)| bit == 1) {
+            for (bit, &row_bit) in data.iter_mut().zip(row) {
+                *bit ^= row_bit;
+            }
+        }
+        data
+    }
+
+    fn extract_data_bits(&self, codeword: &[u8]) -> String {
+        let information_bits = self.information_positions.iter().map(|&position| codeword[position]).collect::<Vec<u8>>();
+        bits_to_string(&self.transform(&information_bits))
+    }
+}
+
+impl BlockCode for LinearBlockCode {
+    fn codeword_bits(&self) -> usize { self.codeword_bits }
+
+    fn data_bits(&self) -> usize { self.generator.len() }
+
+    fn encode(&self, data: &str) -> String {
+        assert_eq!(data.len(), self.generator.len(), "Code encodes {} bits", self.generator.len());
+        bits_to_string(&self.encode_bits(&bits_from_str(data)))
+    }
+
+    fn decode(&self, codeword: &str) -> BlockDecodeResult {
+        assert_eq!(codeword.len(), self.codeword_bits, "Codeword must have {} bits", self.codeword_bits);
+        let mut bits = bits_from_str(codeword);
+        let syndrome = self.parity_check.iter()
+            .enumerate()
+            .fold(0, |syndrome, (index, row)| syndrome | (dot(row, &bits) as usize) << index);
+        let leader = &self.syndrome_table[syndrome];
+        if leader.ambiguous {
+            return BlockDecodeResult {
+                data: self.extract_data_bits(&bits),
+                corrected_positions: Vec::new(),
+                status: DecodeStatus::Uncorrectable,
+            };
+        }
+        for &position in &leader.error_positions {
+            bits[position] ^= 1;
+        }
+        BlockDecodeResult {
+            data: self.extract_data_bits(&bits),
+            corrected_positions: leader.error_positions.clone(),
+            status: if syndrome == 0 { DecodeStatus::Clean } else { DecodeStatus::Corrected },
+        }
+    }
+
+    fn extract_data(&self, codeword: &str) -> String {
+        assert_eq!(codeword.len(), self.codeword_bits, "Codeword must have {} bits", self.codeword_bits);
+        self.extract_data_bits(&bits_from_str(codeword))
+    }
+}
+
+
+fn parse_matrix(rows: &[&str]) -> Vec<Vec<u8>> {
+    assert!(!rows.is_empty(), "Matrix has no rows");
+    rows.iter().map(|row| bits_from_str(row)).collect()
+}
+
+fn unit_rows(data_bits: usize, encode: impl Fn(String) -> String) -> Vec<Vec<u8>> {
+    (0..data_bits).map(|index| {
+        let unit = (0..data_bits).map(|column| if column == index { '1' } else { '0' }).collect();
+        bits_from_str(&encode(unit))
+    }).collect()
+}
+
+fn dot(first: &[u8], second: &[u8]) -> u8 {
+    first.iter().zip(second).fold(0, |sum, (&first, &second)| sum ^ (first & second))
+}
+
+/// Gauss-Jordan elimination over GF(2), looking for pivots in `columns` order.
+/// Returns the pivot column of every nonzero row, zero rows are moved to the end.
+fn row_reduce(rows: &mut [Vec<u8>], columns: impl Iterator<Item = usize>) -> Vec<usize> {
+    let mut pivots = Vec::new();
+    for column in columns {
+        let rank = pivots.len();
+        let Some(pivot_row) = (rank..rows.len()).find(|&row| rows[row][column] == 1) else {
+            continue;
+        };
+        rows.swap(rank, pivot_row);
+        let pivot = rows[rank].clone();
+        for (index, row) in rows.iter_mut().enumerate() {
+            if index != rank && row[column] == 1 {
+                for (bit, &pivot_bit) in row.iter_mut().zip(&pivot) {
+                    *bit ^= pivot_bit;
+                }
+            }
+        }
+        pivots.push(column);
+        if pivots.len() == rows.len() {
+            break;
+        }
+    }
+    pivots
+}
+
+/// Coset leaders by increasing weight until every syndrome has one
+fn build_syndrome_table(parity_check: &[Vec<u8>], codeword_bits: usize) -> Vec<CosetLeader> {
+    let column_syndromes = (0..codeword_bits).map(|column| {
+        parity_check.iter().enumerate().fold(0usize, |syndrome, (index, row)| syndrome | (row[column] as usize) << index)
+    }).collect::<Vec<usize>>();
+
+    let mut table: Vec<Option<CosetLeader>> = vec![None; 1 << parity_check.len()];
+    let mut filled = 0;
+    let mut weight = 0;
+    while filled < table.len() {
+        for_each_combination(codeword_bits, weight, &mut |positions| {
+            let syndrome = positions.iter().fold(0, |syndrome, &position| syndrome ^ column_syndromes[position]);
+            match &mut table[syndrome] {
+                Some(leader) => leader.ambiguous |= leader.error_positions.len() == positions.len(),
+                None => {
+                    table[syndrome] = Some(CosetLeader { error_positions: positions.to_vec(), ambiguous: false });
+                    filled += 1;
+                }
+            }
+        });
+        weight += 1;
+    }
+    table.into_iter().map(Option::unwrap).collect()
+}
+
+/// Calls `action` with every set of `weight` distinct positions below `len`, in increasing order
+pub(crate) fn for_each_combination(len: usize, weight: usize, action: &mut impl FnMut(&[usize])) {
+    fn extend(start: usize, len: usize, weight: usize, positions: &mut Vec<usize>, action: &mut impl FnMut(&[usize])) {
+        if positions.len() == weight {
+            action(positions);
+            return;
+        }
+        for position in start..len {
+            positions.push(position);
+            extend(position + 1, len, weight, positions, action);
+            positions.pop();
+        }
+    }
+    extend(0, len, weight, &mut Vec::with_capacity(weight), action);
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame_fix_algos::bch::BchCode;
+    use crate::test_utils::flip_bits;
+
+    const HAMMING_GENERATOR: [&str; 4] = ["1000110", "0100011", "0010111", "0001101"];
+
+
+    fn all_data(data_bits: usize) -> impl Iterator<Item = String> {
+        (0..1usize << data_bits).map(move |value| format!("{:0width$b}", value, width = data_bits))
+    }
+
+    #[test]
+    fn code_from_generator_matrix() {
+        let code = LinearBlockCode::from_generator(&HAMMING_GENERATOR);
+        assert_eq!((code.codeword_bits(), code.data_bits()), (7, 4));
+        assert_eq!(code.information_positions(), &[0, 1, 2, 3]);
+        assert_eq!(code.parity_check_matrix(), vec!["1011100", "1110010", "0111001"]);
+        assert_eq!(code.minimum_distance(), 3);
+        for data in all_data(4) {
+            let codeword = code.encode(&data);
+            assert_eq!(&codeword[..4], data);
+            assert_eq!(code.syndrome(&codeword), "000");
+        }
+    }
+
+    #[test]
+    fn code_from_parity_check_matrix() {
+        let code = LinearBlockCode::from_parity_check(&["1011100", "1110010", "0111001"]);
+        assert_eq!(code.data_bits(), 4);
+        let from_generator = LinearBlockCode::from_generator(&HAMMING_GENERATOR);
+        for data in all_data(4) {
+            assert_eq!(code.encode(&data), from_generator.encode(&data));
+        }
+    }
+
+    #[test]
+    fn hamming_instance_matches_hamming_module() {
+        let code = LinearBlockCode::hamming(3);
+        assert_eq!((code.codeword_bits(), code.data_bits()), (7, 4));
+        for data in all_data(4) {
+            let codeword = code.encode(&data);
+            assert_eq!(codeword, hamming_code::encode(data.clone()));
+            for position in 0..7 {
+                let result = code.decode(&flip_bits(&codeword, &[position]));
+                assert_eq!(result.data, data);
+                assert_eq!(result.corrected_positions, vec![position]);
+            }
+        }
+        assert_eq!(LinearBlockCode::hamming(4).data_bits(), 11);
+    }
+
+    #[test]
+    fn secded_instance_detects_double_errors() {
+        let code = LinearBlockCode::secded(3);
+        assert_eq!(code.minimum_distance(), 4);
+        let codeword = code.encode("1011");
+        assert_eq!(codeword, hamming_code::encode_extended("1011".to_string()));
+        assert_eq!(code.decode(&flip_bits(&codeword, &[7])).data, "1011");
+        for first in 0..8 {
+            for second in first + 1..8 {
+                assert_eq!(code.decode(&flip_bits(&codeword, &[first, second])).status, DecodeStatus::Uncorrectable);
+            }
+        }
+    }
+
+    #[test]
+    fn non_systematic_generator_converted() {
+        // Строки - суммы строк систематической матрицы
+        let code = LinearBlockCode::from_generator(&["1100101", "0110100", "0011010", "1000110"]);
+        let systematic = code.systematic();
+        assert_eq!(systematic.information_positions(), code.information_positions());
+        for data in all_data(4) {
+            let codeword = code.encode(&data);
+            assert_eq!(code.decode(&codeword).data, data);
+            // Тот же набор кодовых слов, данные лежат на информационных позициях
+            let systematic_data = systematic.information_positions().iter().map(|&index| &codeword[index..index + 1]).collect::<String>();
+            assert_eq!(systematic.encode(&systematic_data), codeword);
+        }
+    }
+
+    #[test]
+    fn repetition_code_corrects_two_errors() {
+        let code = LinearBlockCode::from_generator(&["11111"]);
+        assert_eq!(code.minimum_distance(), 5);
+        let result = code.decode("10100");
+        assert_eq!(result.data, "0");
+        assert_eq!(result.corrected_positions, vec![0, 2]);
+        assert_eq!(code.decode("11011").data, "1");
+    }
+
+    #[test]
+    fn codes_used_through_trait() {
+        let codes: Vec<Box<dyn BlockCode>> = vec![
+            Box::new(LinearBlockCode::hamming(4)),
+            Box::new(LinearBlockCode::secded(3)),
+            Box::new(BchCode::new(4, 2)),
+        ];
+        for code in &codes {
+            let data = "1".repeat(code.data_bits());
+            let codeword = code.encode(&data);
+            assert_eq!(codeword.len(), code.codeword_bits());
+            let result = code.decode(&flip_bits(&codeword, &[1]));
+            assert_eq!(result.data, data);
+            assert_eq!(result.corrected_positions, vec![1]);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Rows of the generator matrix are linearly dependent")]
+    fn dependent_rows_rejected() {
+        LinearBlockCode::from_generator(&["1100", "0110", "1010"]);
+    }
+}
@@ -0,0 +1,418 @@
+// Каскадный код телеметрии CCSDS 131.0-B: внешний код Рида-Соломона (255, 223)
+// с глубиной перемежения I = 1..8 и внутренний свёрточный код K=7, R=1/2.
+// Кодовый блок из I кодовых слов RS передаётся побайтно вперемешку: байт j слова i
+// стоит на позиции j * I + i, поэтому пакет ошибок после декодера Витерби
+// делится между I словами. Первые 223 * I байт блока - данные в исходном порядке.
+// Байты RS по стандарту записаны в дуальном базисе Берлекэмпа, перед кодером и
+// декодером они переводятся в обычный базис матрицей из приложения стандарта.
+// Свёрточный код - генераторы 171 и 133 с инверсией второго выхода, кодовый блок
+// закрывается нулевым хвостом из K-1 бит.
+// Сверка с опубликованными векторами CCSDS 131.0-B, libfec или GNU Radio ещё не
+// сделана: этих векторов в репозитории нет. Тесты сверяют кодовые блоки и полные
+// кадры (RS, перемежение, свёрточный код с инверсией и нулевым хвостом) при I = 1
+// и I = 2 с эталоном, посчитанным отдельной реализацией по алгоритмам
+// encode_rs_ccsds и Taltab / Tal1tab из libfec (GF(2^8) с полиномом 0x187,
+// FCR = 112, шаг корней 11). Это независимая проверка, а не проверка соответствия.
+
+use std::collections::HashMap;
+
+use crate::frame_fix_algos::interleaver::BlockInterleaver;
+use crate::frame_fix_algos::nasa_convo_code::encode_terminated;
+use crate::frame_fix_algos::reed_solomon::{ReedSolomon, RsDecodeResult, RsError};
+use crate::frame_fix_algos::state_machine::{BitSM, CCSDS_GENERATORS};
+use crate::frame_fix_algos::trellis::Trellis;
+use crate::frame_fix_algos::viterbi::{
+    decode_hard_terminated,
+    decode_soft_terminated,
+    BranchMetric,
+    SoftSymbols,
+    Termination
+};
+use crate::utils::{bits_from_str, bits_to_bytes, bits_to_string, bytes_to_bits};
+
+const MAX_INTERLEAVING_DEPTH: usize = 8;
+const CONSTRAINT_LENGTH: usize = 7;
+
+// Строки матрицы перехода из обычного базиса в дуальный, старший бит байта - первая строка
+const DUAL_BASIS_MATRIX: [u8; 8] = [0x8d, 0xef, 0xec, 0x86, 0xfa, 0x99, 0xaf, 0x7b];
+const TO_DUAL_BASIS: [u8; 256] = build_to_dual_basis();
+const FROM_DUAL_BASIS: [u8; 256] = build_from_dual_basis();
+
+
+const fn build_to_dual_basis() -> [u8; 256] {
+    let mut table = [0u8; 256];
+    let mut value = 0;
+    while value < 256 {
+        let mut bit = 0;
+        while bit < 8 {
+            if (value >> bit) & 1 == 1 {
+                table[value] ^= DUAL_BASIS_MATRIX[7 - bit];
+            }
+            bit += 1;
+        }
+        value += 1;
+    }
+    table
+}
+
+const fn build_from_dual_basis() -> [u8; 256] {
+    let mut table = [0u8; 256];
+    let mut value = 0;
+    while value < 256 {
+        table[TO_DUAL_BASIS[value] as usize] = value as u8;
+        value += 1;
+    }
+    table
+}
+
+
+pub struct CcsdsCodec {
+    interleaving_depth: usize,
+    reed_solomon: ReedSolomon,
+    data_interleaver: BlockInterleaver,
+    codeblock_interleaver: BlockInterleaver,
+    states_map: HashMap<String, HashMap<String, String>>,
+    trellis: Trellis,
+}
+
+impl CcsdsCodec {
+    pub fn new(interleaving_depth: usize) -> Self {
+        assert!(
+            (1..=MAX_INTERLEAVING_DEPTH).contains(&interleaving_depth),
+            "CCSDS interleaving depth must be from 1 to {MAX_INTERLEAVING_DEPTH}"
+        );
+        let reed_solomon = ReedSolomon::ccsds();
+        let states_map = BitSM::with_generators(CONSTRAINT_LENGTH, &CCSDS_GENERATORS).states_map();
+        CcsdsCodec {
+            interleaving_depth,
+            data_interleaver: BlockInterleaver::new(interleaving_depth, reed_solomon.data_len()),
+            codeblock_interleaver: BlockInterleaver::new(interleaving_depth, reed_solomon.codeword_len()),
+            reed_solomon,
+            trellis: Trellis::from_states_map(&states_map),
+            states_map,
+        }
+    }
+
+    pub fn interleaving_depth(&self) -> usize { self.interleaving_depth }
+
+    /// Data bytes in one code block, 223 * I
+    pub fn data_len(&self) -> usize { self.data_interleaver.block_len() }
+
+    /// Bytes of the Reed-Solomon code block, 255 * I
+    pub fn codeblock_len(&self) -> usize { self.codeblock_interleaver.block_len() }
+
+    /// Reed-Solomon code block in transmission order, before the convolutional code
+    pub fn encode_codeblock(&self, data: &[u8]) -> Result<Vec<u8>, RsError> {
+        if data.len() != self.data_len() {
+            return Err(RsError::WrongLength);
+        }
+        let mut codewords = Vec::with_capacity(self.codeblock_len());
+        for message in self.data_interleaver.deinterleave(data).chunks(self.reed_solomon.data_len()) {
+            let conventional = message.iter().map(|&byte| FROM_DUAL_BASIS[byte as usize]).collect::<Vec<u8>>();
+            let codeword = self.reed_solomon.encode(&conventional)?;
+            codewords.extend(codeword.iter().map(|&byte| TO_DUAL_BASIS[byte as usize]));
+        }
+        Ok(self.codeblock_interleaver.interleave(&codewords))
+    }
+
+    /// Decode a code block in transmission order. Corrected positions are byte indexes in the code block.
+    pub fn decode_codeblock(&self, codeblock: &[u8]) -> Result<RsDecodeResult, RsError> {
+        if codeblock.len() != self.codeblock_len() {
+            return Err(RsError::WrongLength);
+        }
+        let mut messages = Vec::with_capacity(self.data_len());
+        let mut corrected_positions = Vec::new();
+        let codewords = self.codeblock_interleaver.deinterleave(codeblock);
+        for (codeword_index, codeword) in codewords.chunks(self.reed_solomon.codeword_len()).enumerate() {
+            let conventional = codeword.iter().map(|&byte| FROM_DUAL_BASIS[byte as usize]).collect::<Vec<u8>>();
+            let result = self.reed_solomon.decode(&conventional, &[])?;
+            messages.extend(result.data.iter().map(|&byte| TO_DUAL_BASIS[byte as usize]));
+            corrected_positions.extend(
+                result.corrected_positions.iter().map(|&position| position * self.interleaving_depth + codeword_index)
+            );
+        }
+        corrected_positions.sort_unstable();
+        Ok(RsDecodeResult { data: self.data_interleaver.interleave(&messages), corrected_positions })
+    }
+
+    /// Channel bits of one code block: RS, interleaving and the zero-tailed convolutional code
+    pub fn encode(&self, data: &[u8]) -> Result<String, RsError> {
+        let codeblock = self.encode_codeblock(data)?;
+        let mut bit_sm = BitSM::with_generators(CONSTRAINT_LENGTH, &CCSDS_GENERATORS);
+        let symbols = encode_terminated(bytes_to_bits(&codeblock), Termination::ZeroTail, &mut bit_sm, &self.states_map);
+        let mut bits = bits_from_str(&symbols);
+        invert_second_outputs(&mut bits);
+        Ok(bits_to_string(&bits))
+    }
+
+    pub fn decode(&self, frame: &str) -> Result<RsDecodeResult, RsError> {
+        if frame.len() != self.channel_bits() {
+            return Err(RsError::WrongLength);
+        }
+        let mut bits = bits_from_str(frame);
+        invert_second_outputs(&mut bits);
+        let decoded = decode_hard_terminated(&self.trellis, &bits, Termination::ZeroTail);
+        self.decode_codeblock(&bits_to_bytes(&bits_to_string(&decoded)))
+    }
+
+    pub fn decode_soft(&self, symbols: &SoftSymbols, metric: BranchMetric) -> Result<RsDecodeResult, RsError> {
+        let mut llrs = symbols.to_llrs();
+        if llrs.len() != self.channel_bits() {
+            return Err(RsError::WrongLength);
+        }
+        for llr in llrs.iter_mut().skip(1).step_by(2) {
+            *llr = -*llr;
+        }
+        let decoded = decode_soft_terminated(&self.trellis, &SoftSymbols::Llr(llrs), metric, Termination::ZeroTail);
+        self.decode_codeblock(&bits_to_bytes(&bits_to_string(&decoded)))
+    }
+
+    fn channel_bits(&self) -> usize {
+        2 * (8 * self.codeblock_len() + CONSTRAINT_LENGTH - 1)
+    }
+}
+
+
+/// Inverter on the 133 output, applied the same way before decoding
+fn invert_second_outputs(bits: &mut [u8]) {
+    for bit in bits.iter_mut().skip(1).step_by(2) {
+        *bit ^= 1;
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame_fix_algos::prbs::{PrbsGenerator, PrbsPattern};
+    use crate::test_utils::bpsk_llrs;
+
+    // Начала таблиц Taltab (обычный -> дуальный) и Tal1tab (дуальный -> обычный) по алгоритму libfec
+    const PUBLISHED_TO_DUAL: [u8; 16] = [
+        0x00, 0x7b, 0xaf, 0xd4, 0x99, 0xe2, 0x36, 0x4d, 0xfa, 0x81, 0x55, 0x2e, 0x63, 0x18, 0xcc, 0xb7,
+    ];
+    const PUBLISHED_FROM_DUAL: [u8; 16] = [
+        0x00, 0xcc, 0xac, 0x60, 0x79, 0xb5, 0xd5, 0x19, 0xf0, 0x3c, 0x5c, 0x90, 0x89, 0x45, 0x25, 0xe9,
+    ];
+
+    // Проверочные байты эталонного кодового блока I = 1 для данных 0, 1, ..., 222
+    const REFERENCE_PARITY_DEPTH_1: [u8; 32] = [
+        0x4f, 0xfb, 0x92, 0xdd, 0x55, 0x7e, 0xc6, 0x7f, 0x27, 0xfb, 0x89, 0x82, 0xcf, 0x58, 0xf8, 0xfd,
+        0x02, 0x8a, 0xd1, 0x17, 0xfc, 0xef, 0x6b, 0x27, 0x93, 0xd0, 0x41, 0x88, 0x26, 0x57, 0x86, 0x51,
+    ];
+
+    // Перемежённые проверочные байты эталонного блока I = 2 для данных (7n + 1) mod 256
+    const REFERENCE_PARITY_DEPTH_2: [u8; 64] = [
+        0x87, 0x40, 0x46, 0x90, 0x71, 0xe6, 0xce, 0xe5, 0xf3, 0x67, 0xf8, 0x0a, 0x17, 0xbb, 0x92, 0x54,
+        0x77, 0x91, 0x5c, 0xc2, 0x93, 0xc1, 0xd0, 0x9e, 0xd2, 0xef, 0x49, 0xbb, 0x42, 0x6c, 0xdf, 0xa3,
+        0x5e, 0xff, 0x2c, 0x04, 0xc7, 0x6c, 0x3c, 0x49, 0xa7, 0x06, 0x25, 0xa5, 0x6c, 0x46, 0x4b, 0x2a,
+        0x09, 0xd5, 0x81, 0x5a, 0x19, 0x01, 0x9d, 0xab, 0x37, 0xfa, 0xe3, 0xa5, 0x3a, 0x71, 0x0e, 0xb3,
+    ];
+
+    // Канальные биты эталонного кадра I = 1 (данные 0, 1, ..., 222) в шестнадцатеричной записи
+    const REFERENCE_FRAME_DEPTH_1: &str = "\
+        55555556e92ba49818de926d2e1063a3df0a49b9f5c4b87704318e8232ff7f4c\
+        c05926ea9a97d7246b62e1d15dac101facb63a058678cbcb778dfd3e41430cf0\
+        bd149ba727da6a69d62f5c9ce0e1ad5211fb87483b357686cac04073fc0eb1bd\
+        0ea8e81b546619d5a5932f20935ddeee6247f4f44889053ab97c33cf8fb2c201\
+        48226e91d2ec9f5f2319a9aa15d75864e4cd727ece0383b03ff6b5450938448b\
+        fb9e1d2da150ece350a5da16666b2bd8977101c2bdbff00c4c4ac6f97a843737\
+        86d3a0601c1d51aeede8675bdb2696952a3cbc8f00f24d41f1077bb4c7c98a7a\
+        356fd3dc6fa122129e5414e7a89ae5295980cf33734e3efd82bb0808b475f9c6\
+        9cf9ba4a06374b84f7c27d71c10c8cbf3016a6a51ad8576beb2d619edde39050\
+        2f45c9f6758b3838847e0ecdb2b0ff0343aad519696424d798911222ae5fe3ec\
+        520874bbc8c685753933b3800ffd424efee76854d429999a25dcaf6f13125ea1\
+        e1b40707bb7af6c94a8fc03c7c4131f28d5b1be8a795ea265660dcd360ae2d1d\
+        a73e818d3df07043cc0546b6facbb7780bd19d62211f6cacd0ea5a59e624ab97\
+        1482f2314e4c03ffbfb9350a8977c4c4786deede52a31f10a35629e59598348b\
+        1a91584b03257a6668751b7b55c67d22c6915b151a377097fb71e02c2014822b\
+        4b686ff19aaf76a7e4a63631ca22f348bf821e9106d647919bd840cc5bed992";
+
+    // Канальные биты эталонного кадра I = 2 (данные (7n + 1) mod 256) в шестнадцатеричной записи
+    const REFERENCE_FRAME_DEPTH_2: &str = "\
+        5556e9ca498cc06c10fe4cef5c46c9a619347ee26ea4e4f847d6668acb6eed32\
+        4e1c6f40cf069ccc8c5edebe0e1795068594d77407328d6e1b76fa2a593fbf63\
+        12bcf3e053a6da460ad4821e92b70757d7c58bd49b9211ceb2e093bc059f23c3\
+        80eda1b101f786e69674c494143d5bf74b651985c9c3439fee400f1cac094a55\
+        dc4d3d119d5714b7c4259834889d1d7dcdef45255563df3f7c115d4df0a9d6f5\
+        75db5487f4c1481758850a65dacc41dd514f03afd3e959b520b1c1ed62f884a4\
+        297bc8276861e181311356c5466cd38c031e5f0f4f49c515663b47673e581804\
+        bb2a9a763a30bd21adb3ff532ffa8f2c9fbecd5e1d1897443a9bdbc778d29e8e\
+        e78a06d6a6902f70ffe2a3f3b35a26baf62891fe81b80be4a8ca89962472022e\
+        a100805c201a73d0634231a2e10b7a1a6a883868e82e6272f46a1536b623507f\
+        fda01cfcbcba355ae5c86d027dabe84b38d964c8748efed25dfc7ca0ea83ccdf\
+        6ff14eadeeeb69fa79682b88fb21b4eba479f69926dfac83015ce0004315a549\
+        3351d20d724bfbab2b3977286781f26122f3aa39ba7f3023930db2511fb539e9\
+        9ac7bb9b1bdda70bb799e57935d0aec1be53ecb33cf5b6a9cfad2ef18de46bb8\
+        c667273b877d0e9dde0fb9d9a9703c90ec02b013a0552a098927a87bd144f718\
+        5436756ad52c523d42af104fc0e6603070a22242f2047858d58734db97ce7192\
+        0896e9ca498cc06c10fe4cef5c46c9a619347ee26ea4e4f847d6668acb6eed32\
+        4e1c6f40cf069ccc8c5edebe0e1795068594d77407328d6e1b76fa2a593fbf63\
+        12bcf3e053a6da460ad4821e92b70757d7c58bd49b9211ceb2e093bc059f23c3\
+        80eda1b101f786e69674c494143d5bf74b651985c9c3439fee400f1cac094a55\
+        dc4d3d119d5714b7c4259834889d1d7dcdef45255563df3f7c115d4df0a9d6f5\
+        75db5487f4c1481758850a65dacc41dd514f03afd3e959b520b1c1ed62f884a4\
+        297bc8276861e181311356c5466cd38c031e5f0f4f49c515663b47673e581804\
+        bb2a9a763a30bd21adb3ff532ffa8f2c9fbecd5e1d1897443a9bdbc778d29e8e\
+        e78a06d6a6902f70ffe2a3f3b35a26baf62891fe81b80be4a8ca89962472022e\
+        a100805c201a73d0634231a2e10b7a1a6a883868e82e6272f46a1536b623507f\
+        fda01cfcbcba355ae5c86d027dabe84b38d964c8748efed25dfc7ca0ea83ccdf\
+        6ff14eadeeeb69fa79682b88fb21b4eba479f69926dfac83015ce000a17fe422\
+        6ea78f3513dc833a7794a58737be9de5c69cf9b4bb1f4d56584becd5d4e98346\
+        a18a2a804838bc3d3e42c92fd8fc0e6638ce17968f2c917799eb5205e044d6da\
+        7dc04e6e46b8ea0743cc057e14fe1fd06d5c0ff0dbc775a758b00af57879227a\
+        b13ad1bfcbc585260552a32ac99eb692d2b23930e3893e1c59ff9dc9a8e";
+
+    fn hex_to_bits(hex: &str) -> String {
+        hex.chars().map(|digit| format!("{:04b}", digit.to_digit(16).unwrap())).collect()
+    }
+
+    fn test_data(len: usize) -> Vec<u8> {
+        (0..len).map(|index| (index * 73 + 5) as u8).collect()
+    }
+
+    /// Channel burst: `len` bits of the frame from `start` are XORed with a PRBS
+    fn add_burst(frame: &str, start: usize, len: usize) -> String {
+        let noise = PrbsGenerator::new(PrbsPattern::Prbs15).generate(len);
+        let mut bits = frame.as_bytes().to_vec();
+        for (bit, noise_bit) in bits[start..start + len].iter_mut().zip(noise.bytes()) {
+            *bit ^= noise_bit - b'0';
+        }
+        String::from_utf8(bits).unwrap()
+    }
+
+    #[test]
+    fn dual_basis_tables_are_inverse() {
+        assert_eq!(TO_DUAL_BASIS[0], 0);
+        assert_eq!(TO_DUAL_BASIS[1], 0x7b);
+        assert_eq!(TO_DUAL_BASIS[0x80], 0x8d);
+        for value in 0..=255u8 {
+            assert_eq!(FROM_DUAL_BASIS[TO_DUAL_BASIS[value as usize] as usize], value);
+        }
+        assert_eq!(TO_DUAL_BASIS[..16], PUBLISHED_TO_DUAL);
+        assert_eq!(FROM_DUAL_BASIS[..16], PUBLISHED_FROM_DUAL);
+    }
+
+    #[test]
+    fn reference_codeblock_depth_1() {
+        let codec = CcsdsCodec::new(1);
+        let data = (0..223).map(|index| index as u8).collect::<Vec<u8>>();
+        let mut expected = data.clone();
+        expected.extend_from_slice(&REFERENCE_PARITY_DEPTH_1);
+        assert_eq!(codec.encode_codeblock(&data).unwrap(), expected);
+    }
+
+    #[test]
+    fn reference_codeblock_depth_2() {
+        let codec = CcsdsCodec::new(2);
+        let data = (0..446).map(|index| (index * 7 + 1) as u8).collect::<Vec<u8>>();
+        let mut expected = data.clone();
+        expected.extend_from_slice(&REFERENCE_PARITY_DEPTH_2);
+        assert_eq!(codec.encode_codeblock(&data).unwrap(), expected);
+        assert_eq!(codec.decode_codeblock(&expected).unwrap().data, data);
+    }
+
+    #[test]
+    fn reference_frame_depth_1() {
+        let codec = CcsdsCodec::new(1);
+        let data = (0..223).map(|index| index as u8).collect::<Vec<u8>>();
+        let frame = codec.encode(&data).unwrap();
+        assert_eq!(frame, hex_to_bits(REFERENCE_FRAME_DEPTH_1));
+        assert_eq!(codec.decode(&frame).unwrap().data, data);
+    }
+
+    #[test]
+    fn reference_frame_depth_2() {
+        let codec = CcsdsCodec::new(2);
+        let data = (0..446).map(|index| (index * 7 + 1) as u8).collect::<Vec<u8>>();
+        let frame = codec.encode(&data).unwrap();
+        assert_eq!(frame, hex_to_bits(REFERENCE_FRAME_DEPTH_2));
+        assert_eq!(codec.decode(&frame).unwrap().data, data);
+    }
+
+    #[test]
+    fn inner_code_impulse_response() {
+        // Единица на входе: 171 -> 1111001, 133 -> 1011011 с инверсией -> 0100100, выход 171 первый
+        let codec = CcsdsCodec::new(1);
+        let mut data = vec![0u8; 223];
+        data[0] = 0x80;
+        assert_eq!(&codec.encode(&data).unwrap()[..14], "10111010010010");
+    }
+
+    #[test]
+    fn codeblock_is_systematic_and_interleaved() {
+        let codec = CcsdsCodec::new(4);
+        let data = test_data(codec.data_len());
+        let codeblock = codec.encode_codeblock(&data).unwrap();
+        assert_eq!(codeblock.len(), 1020);
+        assert_eq!(&codeblock[..892], &data[..]);
+
+        // Байт j слова i стоит на позиции j * I + i
+        let codewords = BlockInterleaver::new(4, 255).deinterleave(&codeblock);
+        let first_codeword = codewords[..255].iter().map(|&byte| FROM_DUAL_BASIS[byte as usize]).collect::<Vec<u8>>();
+        assert_eq!(
+            ReedSolomon::ccsds().decode(&first_codeword, &[]).unwrap().data,
+            (0..223).map(|index| FROM_DUAL_BASIS[data[index * 4] as usize]).collect::<Vec<u8>>()
+        );
+    }
+
+    #[test]
+    fn clean_frame_decoded() {
+        let codec = CcsdsCodec::new(1);
+        let data = test_data(223);
+        let frame = codec.encode(&data).unwrap();
+        assert_eq!(frame.len(), 2 * (255 * 8 + 6));
+        let result = codec.decode(&frame).unwrap();
+        assert_eq!(result.data, data);
+        assert!(result.corrected_positions.is_empty());
+    }
+
+    #[test]
+    fn second_output_inverted() {
+        // Нулевые данные кодируются чередованием 0 и 1, а не нулями
+        let codec = CcsdsCodec::new(1);
+        let codeblock = codec.encode_codeblock(&[0; 223]).unwrap();
+        assert!(codeblock.iter().all(|&byte| byte == 0));
+        let frame = codec.encode(&[0; 223]).unwrap();
+        assert_eq!(frame, "01".repeat(frame.len() / 2));
+    }
+
+    #[test]
+    fn long_burst_corrected_only_with_interleaving() {
+        let data_without_interleaving = test_data(223);
+        let codec = CcsdsCodec::new(1);
+        let frame = add_burst(&codec.encode(&data_without_interleaving).unwrap(), 1000, 480);
+        assert_eq!(codec.decode(&frame), Err(RsError::TooManyErrors));
+
+        let codec = CcsdsCodec::new(5);
+        let data = test_data(codec.data_len());
+        let frame = add_burst(&codec.encode(&data).unwrap(), 1000, 480);
+        let result = codec.decode(&frame).unwrap();
+        assert_eq!(result.data, data);
+        assert!(result.corrected_positions.len() > 16);
+    }
+
+    #[test]
+    fn soft_symbols_decoded() {
+        let codec = CcsdsCodec::new(2);
+        let data = test_data(codec.data_len());
+        let frame = codec.encode(&data).unwrap();
+        // Каждый седьмой символ слабо ошибочный
+        let llrs = bpsk_llrs(&frame, |index| if index % 7 == 3 { -0.2 } else { 1.0 });
+        let result = codec.decode_soft(&SoftSymbols::Llr(llrs), BranchMetric::Correlation).unwrap();
+        assert_eq!(result.data, data);
+    }
+
+    #[test]
+    fn wrong_lengths_rejected() {
+        let codec = CcsdsCodec::new(1);
+        assert_eq!(codec.encode(&[0; 10]), Err(RsError::WrongLength));
+        assert_eq!(codec.decode("0101"), Err(RsError::WrongLength));
+    }
+
+    #[test]
+    #[should_panic(expected = "CCSDS interleaving depth must be from 1 to 8")]
+    fn interleaving_depth_limited() {
+        CcsdsCodec::new(9);
+    }
+}
@@ -0,0 +1,244 @@
+// Анализ свойств кодов.
+// Для блочного кода перечисляются все 2^k кодовых слова (как описано в заголовке
+// hamming_code): распределение весов A_i - число кодовых слов веса i. Для
+// линейного кода минимальное расстояние d равно наименьшему весу ненулевого
+// слова, код обнаруживает d - 1 ошибок и исправляет (d - 1) / 2.
+// Ошибка на двоичном симметричном канале с вероятностью p не обнаруживается,
+// если вектор ошибок сам является ненулевым кодовым словом:
+// P_u = sum A_i * p^i * (1 - p)^(n - i), i = 1..n.
+// Для свёрточного кода по решётке перебираются пути, отходящие от нулевого
+// состояния и впервые возвращающиеся в него. Наименьший вес выхода на таком
+// пути - свободное расстояние d_free, число путей с весом d_free + i - члены
+// весового спектра.
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use crate::frame_fix_algos::block_code::BlockCode;
+use crate::frame_fix_algos::trellis::Trellis;
+
+const MAX_ENUMERATED_DATA_BITS: usize = 20;
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodeProperties {
+    pub codeword_bits: usize,
+    pub data_bits: usize,
+    pub minimum_distance: usize,
+    /// Every pattern of up to this many errors is detected
+    pub detectable_errors: usize,
+    /// Every pattern of up to this many errors is corrected by a bounded distance decoder
+    pub correctable_errors: usize,
+    /// Number of codewords of every weight from 0 to n
+    pub weight_distribution: Vec<u64>,
+}
+
+
+/// Number of codewords of every weight, found by encoding all data words
+pub fn weight_distribution(code: &dyn BlockCode) -> Vec<u64> {
+    let data_bits = code.data_bits();
+    assert!(data_bits <= MAX_ENUMERATED_DATA_BITS, "Too many codewords to enumerate");
+    let mut distribution = vec![0u64; code.codeword_bits() + 1];
+    for value in 0u64..1 << data_bits {
+        let data = format!("{:0width$b}", value, width = data_bits);
+        distribution[code.encode(&data).matches('1').count()] += 1;
+    }
+    distribution
+}
+
+
+/// Smallest nonzero weight of a weight distribution, the minimum distance of a linear code
+pub fn minimum_distance(weight_distribution: &[u64]) -> usize {
+    weight_distribution.iter()
+        .enumerate()
+        .skip(1)
+        .find(|(_, &count)| count > 0)
+        .map(|(weight, _)| weight)
+        .expect("Code has no nonzero codewords")
+}
+
+
+pub fn analyze(code: &dyn BlockCode) -> CodeProperties {
+    let weight_distribution = weight_distribution(code);
+    let minimum_distance = minimum_distance(&weight_distribution);
+    CodeProperties {
+        codeword_bits: code.codeword_bits(),
+        data_bits: code.data_bits(),
+        minimum_distance,
+        detectable_errors: minimum_distance - 1,
+        correctable_errors: (minimum_distance - 1) / 2,
+        weight_distribution,
+    }
+}
+
+
+/// Probability that a binary symmetric channel with crossover probability p
+/// turns a codeword into another codeword
+pub fn undetected_error_probability(weight_distribution: &[u64], crossover_probability: f64) -> f64 {
+    assert!((0.0..=1.0).contains(&crossover_probability), "Crossover probability must be in [0, 1]");
+    let codeword_bits = weight_distribution.len() - 1;
+    weight_distribution.iter()
+        .enumerate()
+        .skip(1)
+        .map(|(weight, &count)| {
+            count as f64
+                * crossover_probability.powi(weight as i32)
+                * (1.0 - crossover_probability).powi((codeword_bits - weight) as i32)
+        })
+        .sum()
+}
+
+
+fn output_weight(trellis: &Trellis, state: usize, input_bit: usize) -> usize {
+    trellis.output(state, input_bit).iter().filter(|&&bit| bit == 1).count()
+}
+
+
+/// Free distance of a convolutional code: the smallest output weight of a path
+/// leaving the zero state and merging back into it
+pub fn free_distance(trellis: &Trellis) -> usize {
+    // Дейкстра от первого ненулевого состояния, нулевое состояние - только конец пути
+    let mut distances = vec![usize::MAX; trellis.states_count()];
+    let start = trellis.next_state(0, 1);
+    let mut queue = BinaryHeap::from([Reverse((output_weight(trellis, 0, 1), start))]);
+    while let Some(Reverse((distance, state))) = queue.pop() {
+        if state == 0 {
+            return distance;
+        }
+        if distance >= distances[state] {
+            continue;
+        }
+        distances[state] = distance;
+        for input_bit in 0..2 {
+            let next_state = trellis.next_state(state, input_bit);
+            let next_distance = distance + output_weight(trellis, state, input_bit);
+            if next_distance < distances[next_state] {
+                queue.push(Reverse((next_distance, next_state)));
+            }
+        }
+    }
+    unreachable!("Every path of a shift register returns to the zero state")
+}
+
+
+/// Numbers of paths diverging from the zero state and first merging back with
+/// output weights d_free, d_free + 1, ..., d_free + terms - 1
+pub fn weight_spectrum(trellis: &Trellis, terms: usize) -> Vec<u64> {
+    let free_distance = free_distance(trellis);
+    let max_weight = free_distance + terms - 1;
+    let states_count = trellis.states_count();
+    let mut spectrum = vec![0u64; terms];
+
+    // paths[state][weight] - число путей, ещё не вернувшихся в нулевое состояние
+    let mut paths = vec![vec![0u64; max_weight + 1]; states_count];
+    paths[trellis.next_state(0, 1)][output_weight(trellis, 0, 1)] = 1;
+    // У некатастрофического кода каждый цикл вне нулевого состояния имеет ненулевой вес
+    let max_steps = states_count * (max_weight + 1);
+    for _ in 0..max_steps {
+        let mut next_paths = vec![vec![0u64; max_weight + 1]; states_count];
+        let mut active = false;
+        for (state, weights) in paths.iter().enumerate() {
+            for (weight, &count) in weights.iter().enumerate().filter(|(_, &count)| count > 0) {
+                for input_bit in 0..2 {
+                    let next_state = trellis.next_state(state, input_bit);
+                    let next_weight = weight + output_weight(trellis, state, input_bit);
+                    if next_weight > max_weight {
+                        continue;
+                    }
+                    if next_state == 0 {
+                        spectrum[next_weight - free_distance] += count;
+                    } else {
+                        next_paths[next_state][next_weight] += count;
+                        active = true;
+                    }
+                }
+            }
+        }
+        if !active {
+            return spectrum;
+        }
+        paths = next_paths;
+    }
+    panic!("Catastrophic code: a nonzero path has a zero weight loop");
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame_fix_algos::bch::BchCode;
+    use crate::frame_fix_algos::block_code::LinearBlockCode;
+    use crate::frame_fix_algos::cyclic_code::{CyclicCode, CyclicEncoding};
+    use crate::frame_fix_algos::golay::{ExtendedGolayCode, GolayCode};
+    use crate::frame_fix_algos::hamming_code::HammingBlockCode;
+    use crate::frame_fix_algos::ldpc::LdpcCode;
+    use crate::frame_fix_algos::state_machine::{BitSM, GSM_GENERATORS, LTE_GENERATORS};
+
+    #[test]
+    fn hamming_code_properties() {
+        let properties = analyze(&LinearBlockCode::hamming(3));
+        assert_eq!(properties.weight_distribution, vec![1, 0, 0, 7, 7, 0, 0, 1]);
+        assert_eq!(properties.minimum_distance, 3);
+        assert_eq!(properties.detectable_errors, 2);
+        assert_eq!(properties.correctable_errors, 1);
+
+        let cyclic = analyze(&CyclicCode::hamming_7_4(CyclicEncoding::NonSystematic));
+        assert_eq!(cyclic.weight_distribution, properties.weight_distribution);
+
+        let bit_level = analyze(&HammingBlockCode::new(7, 4));
+        assert_eq!(bit_level, properties);
+    }
+
+    #[test]
+    fn minimum_distance_of_crate_codes() {
+        let codes: Vec<(Box<dyn BlockCode>, usize)> = vec![
+            (Box::new(LinearBlockCode::secded(3)), 4),
+            (Box::new(BchCode::new(4, 2)), 5),
+            (Box::new(BchCode::new(5, 3)), 7),
+            (Box::new(GolayCode), 7),
+            (Box::new(ExtendedGolayCode), 8),
+            (Box::new(HammingBlockCode::new(15, 11)), 3),
+            (Box::new(LdpcCode::from_dense(&["1010101", "0110011", "0001111"])), 3),
+        ];
+        for (code, distance) in codes {
+            let properties = analyze(code.as_ref());
+            assert_eq!(properties.minimum_distance, distance);
+            assert_eq!(properties.weight_distribution.iter().sum::<u64>(), 1 << code.data_bits());
+        }
+    }
+
+    #[test]
+    fn undetected_error_probability_on_bsc() {
+        let distribution = weight_distribution(&LinearBlockCode::hamming(3));
+        assert_eq!(undetected_error_probability(&distribution, 0.0), 0.0);
+        // При p = 1/2 все 2^n векторов равновероятны: (2^k - 1) / 2^n
+        assert!((undetected_error_probability(&distribution, 0.5) - 15.0 / 128.0).abs() < 1e-12);
+
+        let p: f64 = 1e-3;
+        let expected = 7.0 * p.powi(3) * (1.0 - p).powi(4) + 7.0 * p.powi(4) * (1.0 - p).powi(3) + p.powi(7);
+        assert!((undetected_error_probability(&distribution, p) - expected).abs() < 1e-18);
+    }
+
+    #[test]
+    fn nasa_code_free_distance_and_spectrum() {
+        let trellis = Trellis::from_states_map(&BitSM::new().states_map());
+        assert_eq!(free_distance(&trellis), 10);
+        assert_eq!(weight_spectrum(&trellis, 5), vec![11, 0, 38, 0, 193]);
+    }
+
+    #[test]
+    fn free_distance_of_other_generators() {
+        let gsm = Trellis::from_states_map(&BitSM::with_generators(5, &GSM_GENERATORS).states_map());
+        assert_eq!(free_distance(&gsm), 7);
+        let lte = Trellis::from_states_map(&BitSM::with_generators(7, &LTE_GENERATORS).states_map());
+        assert_eq!(free_distance(&lte), 15);
+    }
+
+    #[test]
+    #[should_panic(expected = "Catastrophic code: a nonzero path has a zero weight loop")]
+    fn catastrophic_code_detected() {
+        // 6 = 110, 5 = 101: при входе 111... выход из ненулевого состояния нулевой
+        let trellis = Trellis::from_states_map(&BitSM::with_generators(3, &[0o6, 0o5]).states_map());
+        weight_spectrum(&trellis, 3);
+    }
+}
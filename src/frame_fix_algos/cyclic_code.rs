@@ -0,0 +1,343 @@
+// Циклические коды (n, k) с порождающим полиномом g(x) степени n - k, делящим
+// x^n - 1. Строка бит - коэффициенты от старшего к младшему.
+// Систематическое кодирование: данные, за ними остаток от деления d(x) * x^(n-k)
+// на g(x); несистематическое: c(x) = d(x) * g(x).
+// Синдром считается моделью делящего регистра сдвига: биты принятого слова
+// вдвигаются старшим вперёд, при выходе единицы из старшей ячейки регистр
+// складывается с g(x). Циклический сдвиг слова на один бит соответствует
+// умножению синдрома на x по модулю g(x), на этом построены оба декодера:
+// - декодер Меггита проверяет, совпадает ли синдром с синдромом какого-либо
+//   исправимого вектора ошибок с ошибкой в старшем разряде, и исправляет
+//   старший разряд, сдвигая слово n раз;
+// - вылавливание ошибок (error trapping) сдвигает слово, пока весь пакет ошибок
+//   не окажется в младших b разрядах, тогда вектор ошибок равен синдрому.
+
+use std::collections::HashSet;
+
+use crate::frame_fix_algos::block_code::{for_each_combination, BlockCode};
+use crate::frame_fix_algos::decode_report::{BlockDecodeResult, DecodeStatus};
+use crate::utils::{bits_from_str, bits_to_string};
+
+const MAX_PARITY_BITS: usize = 63;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CyclicEncoding {
+    /// Data bits followed by the remainder of d(x) * x^(n-k) divided by g(x)
+    Systematic,
+    /// Product d(x) * g(x)
+    NonSystematic,
+}
+
+
+pub struct CyclicCode {
+    codeword_bits: usize,
+    parity_bits: usize,
+    /// g(x) with the x^(n-k) term, bit i is the coefficient of x^i
+    generator: u64,
+    encoding: CyclicEncoding,
+    correctable_errors: usize,
+    /// Syndromes of correctable error patterns with an error in the highest degree
+    meggitt_syndromes: HashSet<u64>,
+}
+
+impl CyclicCode {
+    /// Cyclic code of length n from a generator like "1011" (x^3 + x + 1).
+    /// The Meggitt decoder corrects all patterns of up to `correctable_errors` errors.
+    pub fn new(codeword_bits: usize, generator: &str, encoding: CyclicEncoding, correctable_errors: usize) -> Self {
+        let generator_bits = generator.trim_start_matches('0');
+        assert!(generator_bits.len() >= 2, "Generator polynomial must have a positive degree");
+        let parity_bits = generator_bits.len() - 1;
+        assert!(parity_bits <= MAX_PARITY_BITS, "Syndrome register is limited to {MAX_PARITY_BITS} bits");
+        assert!(parity_bits < codeword_bits, "Generator degree must be less than the code length");
+        assert!(generator_bits.ends_with('1'), "Generator polynomial must have a constant term");
+
+        let mut code = CyclicCode {
+            codeword_bits,
+            parity_bits,
+            generator: u64::from_str_radix(generator_bits, 2).unwrap(),
+            encoding,
+            correctable_errors,
+            meggitt_syndromes: HashSet::new(),
+        };
+        // g(x) должен делить x^n - 1, иначе сдвиг кодового слова не будет кодовым словом
+        let mut cycle = vec![0u8; codeword_bits + 1];
+        cycle[0] = 1;
+        cycle[codeword_bits] = 1;
+        assert_eq!(code.remainder(&cycle), 0, "Generator does not divide x^{codeword_bits} - 1");
+
+        let power_remainders = code.power_remainders();
+        let mut syndromes = HashSet::new();
+        for weight in 1..=correctable_errors {
+            for_each_combination(codeword_bits - 1, weight - 1, &mut |degrees| {
+                let syndrome = degrees.iter()
+                    .fold(power_remainders[codeword_bits - 1], |syndrome, &degree| syndrome ^ power_remainders[degree]);
+                syndromes.insert(syndrome);
+            });
+        }
+        code.meggitt_syndromes = syndromes;
+        code
+    }
+
+    /// Cyclic Hamming (7, 4) code with g(x) = x^3 + x + 1
+    pub fn hamming_7_4(encoding: CyclicEncoding) -> Self {
+        Self::new(7, "1011", encoding, 1)
+    }
+
+    pub fn correctable_errors(&self) -> usize { self.correctable_errors }
+
+    /// Remainder of the division by g(x) in a shift register, bits enter highest degree first
+    fn remainder(&self, bits: &[u8]) -> u64 {
+        let mask = (1u64 << self.parity_bits) - 1;
+        bits.iter().fold(0, |register, &bit| {
+            let feedback = (register >> (self.parity_bits - 1)) & 1;
+            let register = ((register << 1) | bit as u64) & mask;
+            if feedback == 1 { register ^ (self.generator & mask) } else { register }
+        })
+    }
+
+    /// x * syndrome mod g(x), the syndrome of the word cyclically shifted by one bit
+    fn shift_syndrome(&self, syndrome: u64) -> u64 {
+        let shifted = syndrome << 1;
+        if (shifted >> self.parity_bits) & 1 == 1 { shifted ^ self.generator } else { shifted }
+    }
+
+    /// x^degree mod g(x) for every degree of the codeword
+    fn power_remainders(&self) -> Vec<u64> {
+        let mut remainders = Vec::with_capacity(self.codeword_bits);
+        let mut remainder = 1;
+        for _ in 0..self.codeword_bits {
+            remainders.push(remainder);
+            remainder = self.shift_syndrome(remainder);
+        }
+        remainders
+    }
+
+    pub fn syndrome(&self, codeword: &str) -> String {
+        assert_eq!(codeword.len(), self.codeword_bits, "Cyclic codeword must have {} bits", self.codeword_bits);
+        format!("{:0width$b}", self.remainder(&bits_from_str(codeword)), width = self.parity_bits)
+    }
+
+    fn generator_bits(&self) -> Vec<u8> {
+        (0..=self.parity_bits).rev().map(|degree| ((self.generator >> degree) & 1) as u8).collect()
+    }
+
+    fn extract_data_bits(&self, codeword: &[u8]) -> String {
+        let data_bits = self.codeword_bits - self.parity_bits;
+        match self.encoding {
+            CyclicEncoding::Systematic => bits_to_string(&codeword[..data_bits]),
+            CyclicEncoding::NonSystematic => {
+                // Деление столбиком: частное c(x) / g(x)
+                let generator = self.generator_bits();
+                let mut remainder = codeword.to_vec();
+                let quotient = (0..data_bits).map(|index| {
+                    let bit = remainder[index];
+                    if bit == 1 {
+                        for (offset, &generator_bit) in generator.iter().enumerate() {
+                            remainder[index + offset] ^= generator_bit;
+                        }
+                    }
+                    bit
+                }).collect::<Vec<u8>>();
+                bits_to_string(&quotient)
+            }
+        }
+    }
+
+    fn result(&self, received: &[u8], errors: Vec<usize>, syndrome: u64) -> BlockDecodeResult {
+        let mut bits = received.to_vec();
+        for &position in &errors {
+            bits[position] ^= 1;
+        }
+        BlockDecodeResult {
+            data: self.extract_data_bits(&bits),
+            status: if syndrome == 0 { DecodeStatus::Clean } else { DecodeStatus::Corrected },
+            corrected_positions: errors,
+        }
+    }
+
+    fn uncorrectable(&self, received: &[u8]) -> BlockDecodeResult {
+        BlockDecodeResult {
+            data: self.extract_data_bits(received),
+            corrected_positions: Vec::new(),
+            status: DecodeStatus::Uncorrectable,
+        }
+    }
+
+    /// Meggitt decoding: the highest bit of every cyclic shift is corrected
+    /// when the syndrome belongs to a correctable pattern with an error there
+    pub fn decode_meggitt(&self, codeword: &str) -> BlockDecodeResult {
+        assert_eq!(codeword.len(), self.codeword_bits, "Cyclic codeword must have {} bits", self.codeword_bits);
+        let received = bits_from_str(codeword);
+        let initial_syndrome = self.remainder(&received);
+        let top_remainder = self.power_remainders()[self.codeword_bits - 1];
+
+        let mut syndrome = initial_syndrome;
+        let mut errors = Vec::new();
+        for index in 0..self.codeword_bits {
+            if syndrome == 0 {
+                break;
+            }
+            // После сдвига на index старший разряд - бит строки с индексом index
+            if self.meggitt_syndromes.contains(&syndrome) {
+                errors.push(index);
+                syndrome ^= top_remainder;
+            }
+            syndrome = self.shift_syndrome(syndrome);
+        }
+        if syndrome != 0 {
+            return self.uncorrectable(&received);
+        }
+        self.result(&received, errors, initial_syndrome)
+    }
+
+    /// Error trapping for a burst of up to `burst_len` bits, including bursts wrapping around the end
+    pub fn decode_burst(&self, codeword: &str, burst_len: usize) -> BlockDecodeResult {
+        assert_eq!(codeword.len(), self.codeword_bits, "Cyclic codeword must have {} bits", self.codeword_bits);
+        assert!(burst_len <= self.parity_bits, "Trapped burst can't be longer than {} bits", self.parity_bits);
+        let received = bits_from_str(codeword);
+        let initial_syndrome = self.remainder(&received);
+
+        let mut syndrome = initial_syndrome;
+        for shift in 0..self.codeword_bits {
+            if syndrome >> burst_len == 0 {
+                // Вектор ошибок сдвинутого слова равен синдрому, разряд degree - бит строки n-1-degree+shift
+                let mut errors = (0..burst_len)
+                    .filter(|&degree| (syndrome >> degree) & 1 == 1)
+                    .map(|degree| (self.codeword_bits - 1 - degree + shift) % self.codeword_bits)
+                    .collect::<Vec<usize>>();
+                errors.sort_unstable();
+                return self.result(&received, errors, initial_syndrome);
+            }
+            syndrome = self.shift_syndrome(syndrome);
+        }
+        self.uncorrectable(&received)
+    }
+}
+
+impl BlockCode for CyclicCode {
+    fn codeword_bits(&self) -> usize { self.codeword_bits }
+
+    fn data_bits(&self) -> usize { self.codeword_bits - self.parity_bits }
+
+    fn encode(&self, data: &str) -> String {
+        let data_bits = self.data_bits();
+        assert_eq!(data.len(), data_bits, "Cyclic code encodes {data_bits} bits");
+        let data = bits_from_str(data);
+        match self.encoding {
+            CyclicEncoding::Systematic => {
+                let mut message = data.clone();
+                message.resize(self.codeword_bits, 0);
+                format!("{}{}", bits_to_string(&data), format_args!("{:0width$b}", self.remainder(&message), width = self.parity_bits))
+            }
+            CyclicEncoding::NonSystematic => {
+                let mut codeword = vec![0u8; self.codeword_bits];
+                for (index, _) in data.iter().enumerate().filter(|(_, &bit)| bit == 1) {
+                    for (offset, generator_bit) in self.generator_bits().into_iter().enumerate() {
+                        codeword[index + offset] ^= generator_bit;
+                    }
+                }
+                bits_to_string(&codeword)
+            }
+        }
+    }
+
+    fn decode(&self, codeword: &str) -> BlockDecodeResult {
+        self.decode_meggitt(codeword)
+    }
+
+    fn extract_data(&self, codeword: &str) -> String {
+        assert_eq!(codeword.len(), self.codeword_bits, "Cyclic codeword must have {} bits", self.codeword_bits);
+        self.extract_data_bits(&bits_from_str(codeword))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame_fix_algos::bch::BchCode;
+    use crate::test_utils::flip_bits;
+
+
+    #[test]
+    fn hamming_7_4_encodings() {
+        let systematic = CyclicCode::hamming_7_4(CyclicEncoding::Systematic);
+        assert_eq!(systematic.encode("1001"), "1001110");
+        let non_systematic = CyclicCode::hamming_7_4(CyclicEncoding::NonSystematic);
+        assert_eq!(non_systematic.encode("1001"), "1010011");
+        assert_eq!(non_systematic.decode("1010011").data, "1001");
+    }
+
+    #[test]
+    fn cyclic_shifts_are_codewords() {
+        let code = CyclicCode::hamming_7_4(CyclicEncoding::Systematic);
+        let codeword = code.encode("1101");
+        for shift in 0..7 {
+            let shifted = format!("{}{}", &codeword[shift..], &codeword[..shift]);
+            assert_eq!(code.syndrome(&shifted), "000");
+        }
+        assert_eq!(code.syndrome(&flip_bits(&codeword, &[6])), "001");
+    }
+
+    #[test]
+    fn meggitt_corrects_single_errors_in_both_encodings() {
+        for encoding in [CyclicEncoding::Systematic, CyclicEncoding::NonSystematic] {
+            let code = CyclicCode::hamming_7_4(encoding);
+            let codeword = code.encode("0111");
+            for position in 0..7 {
+                let result = code.decode(&flip_bits(&codeword, &[position]));
+                assert_eq!(result.data, "0111");
+                assert_eq!(result.corrected_positions, vec![position]);
+                assert_eq!(result.status, DecodeStatus::Corrected);
+            }
+            assert_eq!(code.decode(&codeword).status, DecodeStatus::Clean);
+        }
+    }
+
+    #[test]
+    fn meggitt_corrects_double_errors_of_bch_15_7() {
+        let code = CyclicCode::new(15, "111010001", CyclicEncoding::Systematic, 2);
+        let bch = BchCode::new(4, 2);
+        let codeword = code.encode("1011001");
+        assert_eq!(codeword, bch.encode("1011001"));
+        for first in 0..15 {
+            for second in first + 1..15 {
+                let result = code.decode(&flip_bits(&codeword, &[first, second]));
+                assert_eq!(result.data, "1011001");
+                assert_eq!(result.corrected_positions, vec![first, second]);
+            }
+        }
+    }
+
+    #[test]
+    fn error_trapping_corrects_bursts() {
+        // (15, 9) код, исправляющий пакеты длиной до 3
+        let code = CyclicCode::new(15, "1111001", CyclicEncoding::Systematic, 1);
+        let codeword = code.encode("110010111");
+        for start in 0..15 {
+            for burst in [vec![0], vec![0, 1], vec![0, 2], vec![0, 1, 2]] {
+                let positions = burst.iter().map(|offset| (start + offset) % 15).collect::<Vec<usize>>();
+                let result = code.decode_burst(&flip_bits(&codeword, &positions), 3);
+                assert_eq!(result.data, "110010111");
+                let mut sorted_positions = positions.clone();
+                sorted_positions.sort_unstable();
+                assert_eq!(result.corrected_positions, sorted_positions);
+            }
+        }
+    }
+
+    #[test]
+    fn uncorrectable_pattern_reported() {
+        let code = CyclicCode::new(15, "111010001", CyclicEncoding::Systematic, 2);
+        let codeword = code.encode("0000001");
+        let result = code.decode_meggitt(&flip_bits(&codeword, &[0, 4, 9]));
+        assert_eq!(result.status, DecodeStatus::Uncorrectable);
+        assert!(result.corrected_positions.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Generator does not divide x^8 - 1")]
+    fn generator_must_divide_cycle_polynomial() {
+        CyclicCode::new(8, "1011", CyclicEncoding::Systematic, 1);
+    }
+}
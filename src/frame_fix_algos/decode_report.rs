@@ -0,0 +1,21 @@
+/// Outcome of decoding a single codeword
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeStatus {
+    /// No errors found
+    Clean,
+    /// Errors found and corrected
+    Corrected,
+    /// Errors found which the code can only detect
+    Uncorrectable,
+}
+
+
+/// Decoded data of a block code together with the codeword bits it flipped
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockDecodeResult {
+    /// Data bits after correction
+    pub data: String,
+    /// Zero-based indexes of corrected bits in the codeword
+    pub corrected_positions: Vec<usize>,
+    pub status: DecodeStatus,
+}
@@ -0,0 +1,248 @@
+// Двоичный код Голея (23, 12) - совершенный код с минимальным расстоянием 7,
+// исправляющий любые 3 ошибки. Порождающий полином
+// g(x) = x^11 + x^10 + x^6 + x^5 + x^4 + x^2 + 1 (0xC75).
+// Кодирование систематическое: 12 бит данных, за ними 11 проверочных бит -
+// остаток от деления d(x) * x^11 на g(x), первый бит строки - старший коэффициент.
+// Код совершенный, поэтому каждый из 2^11 синдромов соответствует ровно одному
+// вектору ошибок веса не больше 3, и таблица синдромов считается при компиляции.
+// Расширенный код (24, 12) добавляет в конец бит общей чётности: 3 ошибки
+// исправляются, 4 обнаруживаются.
+
+use crate::frame_fix_algos::block_code::BlockCode;
+use crate::frame_fix_algos::decode_report::{BlockDecodeResult, DecodeStatus};
+
+const GENERATOR: u32 = 0xC75;
+const CODEWORD_BITS: usize = 23;
+const DATA_BITS: usize = 12;
+const PARITY_BITS: usize = 11;
+const EXTENDED_CODEWORD_BITS: usize = 24;
+const CORRECTABLE_ERRORS: u32 = 3;
+
+const SYNDROME_TABLE: [u32; 1 << PARITY_BITS] = build_syndrome_table();
+
+
+/// Remainder of the polynomial with coefficients `bits` divided by g(x)
+const fn remainder(mut bits: u32) -> u32 {
+    let mut degree = CODEWORD_BITS;
+    while degree > PARITY_BITS {
+        degree -= 1;
+        if (bits >> degree) & 1 == 1 {
+            bits ^= GENERATOR << (degree - PARITY_BITS);
+        }
+    }
+    bits
+}
+
+/// Error pattern of weight up to 3 for every syndrome
+const fn build_syndrome_table() -> [u32; 1 << PARITY_BITS] {
+    let mut table = [0u32; 1 << PARITY_BITS];
+    // Индексы от 23 до 25 - "пустые" позиции, они дают векторы веса меньше 3
+    let mut first = 0;
+    while first < CODEWORD_BITS + 1 {
+        let mut second = first + 1;
+        while second < CODEWORD_BITS + 2 {
+            let mut third = second + 1;
+            while third < CODEWORD_BITS + 3 {
+                let pattern = error_bit(first) | error_bit(second) | error_bit(third);
+                table[remainder(pattern) as usize] = pattern;
+                third += 1;
+            }
+            second += 1;
+        }
+        first += 1;
+    }
+    table
+}
+
+const fn error_bit(degree: usize) -> u32 {
+    if degree < CODEWORD_BITS { 1 << degree } else { 0 }
+}
+
+
+fn bits_to_word(bits: &str) -> u32 {
+    bits.bytes().fold(0, |word, bit| (word << 1) | (bit - b'0') as u32)
+}
+
+fn word_to_bits(word: u32, len: usize) -> String {
+    format!("{:0len$b}", word, len = len)
+}
+
+/// Zero-based string indexes of the set bits of an error pattern
+fn error_positions(pattern: u32) -> Vec<usize> {
+    (0..CODEWORD_BITS).rev()
+        .filter(|&degree| (pattern >> degree) & 1 == 1)
+        .map(|degree| CODEWORD_BITS - 1 - degree)
+        .collect()
+}
+
+
+pub fn encode(data: &str) -> String {
+    assert_eq!(data.len(), DATA_BITS, "Golay code encodes {DATA_BITS} bits");
+    let message = bits_to_word(data) << PARITY_BITS;
+    word_to_bits(message | remainder(message), CODEWORD_BITS)
+}
+
+
+/// Decode a Golay codeword correcting up to 3 errors
+pub fn decode(codeword: &str) -> BlockDecodeResult {
+    assert_eq!(codeword.len(), CODEWORD_BITS, "Golay codeword must have {CODEWORD_BITS} bits");
+    let received = bits_to_word(codeword);
+    let pattern = SYNDROME_TABLE[remainder(received) as usize];
+    let corrected = received ^ pattern;
+    BlockDecodeResult {
+        data: word_to_bits(corrected >> PARITY_BITS, DATA_BITS),
+        corrected_positions: error_positions(pattern),
+        status: if pattern == 0 { DecodeStatus::Clean } else { DecodeStatus::Corrected },
+    }
+}
+
+
+/// Golay codeword followed by an overall parity bit
+pub fn encode_extended(data: &str) -> String {
+    let codeword = encode(data);
+    let parity = if codeword.matches('1').count() % 2 == 1 { '1' } else { '0' };
+    format!("{}{}", codeword, parity)
+}
+
+
+/// Decode an extended Golay codeword. Up to 3 errors are corrected, 4 errors are
+/// reported as uncorrectable and the data bits are returned as received.
+pub fn decode_extended(codeword: &str) -> BlockDecodeResult {
+    assert_eq!(codeword.len(), EXTENDED_CODEWORD_BITS, "Extended Golay codeword must have {EXTENDED_CODEWORD_BITS} bits");
+    let received = bits_to_word(&codeword[..CODEWORD_BITS]);
+    let parity_failed = codeword.matches('1').count() % 2 == 1;
+    let pattern = SYNDROME_TABLE[remainder(received) as usize];
+    let weight = pattern.count_ones();
+
+    // Исправление pattern меняет чётность на weight, остаток должен дать ошибку в бите чётности
+    let parity_bit_error = parity_failed != (weight % 2 == 1);
+    if parity_bit_error && weight == CORRECTABLE_ERRORS {
+        return BlockDecodeResult {
+            data: codeword[..DATA_BITS].to_string(),
+            corrected_positions: Vec::new(),
+            status: DecodeStatus::Uncorrectable,
+        };
+    }
+
+    let mut corrected_positions = error_positions(pattern);
+    if parity_bit_error {
+        corrected_positions.push(CODEWORD_BITS);
+    }
+    BlockDecodeResult {
+        data: word_to_bits((received ^ pattern) >> PARITY_BITS, DATA_BITS),
+        status: if corrected_positions.is_empty() { DecodeStatus::Clean } else { DecodeStatus::Corrected },
+        corrected_positions,
+    }
+}
+
+
+/// Golay (23, 12) code behind the common block code interface
+pub struct GolayCode;
+
+impl BlockCode for GolayCode {
+    fn codeword_bits(&self) -> usize { CODEWORD_BITS }
+
+    fn data_bits(&self) -> usize { DATA_BITS }
+
+    fn encode(&self, data: &str) -> String { encode(data) }
+
+    fn decode(&self, codeword: &str) -> BlockDecodeResult { decode(codeword) }
+}
+
+
+/// Extended Golay (24, 12) code behind the common block code interface
+pub struct ExtendedGolayCode;
+
+impl BlockCode for ExtendedGolayCode {
+    fn codeword_bits(&self) -> usize { EXTENDED_CODEWORD_BITS }
+
+    fn data_bits(&self) -> usize { DATA_BITS }
+
+    fn encode(&self, data: &str) -> String { encode_extended(data) }
+
+    fn decode(&self, codeword: &str) -> BlockDecodeResult { decode_extended(codeword) }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::flip_bits;
+
+    #[test]
+    fn generator_is_a_codeword() {
+        assert_eq!(encode("000000000001"), "00000000000110001110101");
+        assert_eq!(encode("000000000000"), "0".repeat(23));
+    }
+
+    #[test]
+    fn weight_distribution_of_perfect_code() {
+        let mut weights = [0usize; 25];
+        let mut extended_weights = [0usize; 25];
+        for value in 0..1 << DATA_BITS {
+            let data = format!("{:012b}", value);
+            weights[encode(&data).matches('1').count()] += 1;
+            extended_weights[encode_extended(&data).matches('1').count()] += 1;
+        }
+        assert_eq!(&weights[..9], &[1, 0, 0, 0, 0, 0, 0, 253, 506]);
+        assert_eq!(extended_weights[8], 759);
+        assert_eq!(extended_weights[12], 2576);
+        assert!(extended_weights.iter().enumerate().all(|(weight, &count)| count == 0 || weight % 4 == 0));
+    }
+
+    #[test]
+    fn all_patterns_up_to_three_errors_corrected() {
+        let data = "101100111000";
+        let codeword = encode(data);
+        for first in 0..23 {
+            for second in first + 1..23 {
+                for third in second + 1..23 {
+                    let result = decode(&flip_bits(&codeword, &[first, second, third]));
+                    assert_eq!(result.data, data);
+                    assert_eq!(result.corrected_positions, vec![first, second, third]);
+                    assert_eq!(result.status, DecodeStatus::Corrected);
+                }
+            }
+        }
+        for first in 0..23 {
+            for second in first + 1..23 {
+                assert_eq!(decode(&flip_bits(&codeword, &[first, second])).corrected_positions, vec![first, second]);
+            }
+            assert_eq!(decode(&flip_bits(&codeword, &[first])).corrected_positions, vec![first]);
+        }
+        let result = decode(&codeword);
+        assert_eq!(result.status, DecodeStatus::Clean);
+        assert_eq!(result.data, data);
+    }
+
+    #[test]
+    fn extended_code_corrects_three_errors_with_parity_bit() {
+        let data = "010011100101";
+        let codeword = encode_extended(data);
+        assert_eq!(decode_extended(&codeword).status, DecodeStatus::Clean);
+
+        let result = decode_extended(&flip_bits(&codeword, &[2, 15, 23]));
+        assert_eq!(result.data, data);
+        assert_eq!(result.corrected_positions, vec![2, 15, 23]);
+
+        let result = decode_extended(&flip_bits(&codeword, &[0, 11, 22]));
+        assert_eq!(result.data, data);
+        assert_eq!(result.status, DecodeStatus::Corrected);
+    }
+
+    #[test]
+    fn extended_code_detects_four_errors() {
+        let codeword = encode_extended("111000111000");
+        for errors in [[0, 1, 2, 3], [5, 9, 17, 22], [1, 12, 20, 23]] {
+            let result = decode_extended(&flip_bits(&codeword, &errors));
+            assert_eq!(result.status, DecodeStatus::Uncorrectable);
+            assert!(result.corrected_positions.is_empty());
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Golay code encodes 12 bits")]
+    fn wrong_data_length_rejected() {
+        encode("1011");
+    }
+}
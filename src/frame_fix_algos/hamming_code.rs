@@ -14,6 +14,13 @@
 // в смысле Хэмминга.
 
 
+// Расширенный код Хэмминга (SECDED) добавляет в конец кодового слова бит общей
+// чётности. Одиночная ошибка меняет общую чётность и исправляется по синдрому,
+// двойная ошибка оставляет чётность прежней при ненулевом синдроме - такая
+// ошибка только обнаруживается.
+
+use crate::frame_fix_algos::block_code::BlockCode;
+use crate::frame_fix_algos::decode_report::{BlockDecodeResult, DecodeStatus};
 use crate::utils::power_of_two;
 
 fn get_redundant_bit_count(frame_len: u32) -> usize {
@@ -24,19 +31,19 @@ fn get_redundant_bit_count(frame_len: u32) -> usize {
     bit_to_check as usize
 }
 
-fn encode(mut frame: String) -> String {
+pub fn encode(mut frame: String) -> String {
     let mut control_bit_sum: usize = 0;
     // Предварительное заполнение выходного вектора битов
     let redundant_bits = get_redundant_bit_count(frame.len() as u32);
     let mut result_vec = vec!['0';frame.len() + redundant_bits];
 
     // Заполнение выходного вектора битами данных
-    for index in 0..result_vec.len() {
+    for (index, result_bit) in result_vec.iter_mut().enumerate() {
         if !power_of_two(index + 1) {
             let next_data_bit = frame.remove(0);
             if next_data_bit == '1' {
                 control_bit_sum ^= index + 1;
-                result_vec[index] = next_data_bit;
+                *result_bit = next_data_bit;
             };
        };
     };
@@ -51,60 +58,237 @@ fn encode(mut frame: String) -> String {
 }
 
 
-fn decode(frame: String) -> (String, usize) {
-    let mut control_bit_sum: usize = 0;
-    let mut result_frame = String::new();
-    for index in 0..frame.len() {
-        let next_frame_bit = frame.as_bytes()[index] as char;
-        if next_frame_bit == '1' {
-            control_bit_sum ^= index + 1;
+/// Where the corrected error was, as zero-based indexes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorLocation {
+    Data { codeword_index: usize, data_index: usize },
+    Parity { codeword_index: usize },
+    /// Overall parity bit of the extended code
+    OverallParity { codeword_index: usize },
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HammingDecodeResult {
+    /// Data bits after correction
+    pub data: String,
+    /// XOR of the one-based positions of all ones, equal to the position of a single error
+    pub syndrome: usize,
+    pub error_location: Option<ErrorLocation>,
+    pub status: DecodeStatus,
+}
+
+
+fn get_syndrome(codeword: &str) -> usize {
+    codeword.chars()
+        .enumerate()
+        .filter(|(_, bit)| *bit == '1')
+        .fold(0, |syndrome, (index, _)| syndrome ^ (index + 1))
+}
+
+
+fn flip_bit(codeword: &str, index: usize) -> String {
+    let mut fixed_codeword = codeword.to_string();
+    let fixed_bit = if codeword.as_bytes()[index] == b'1' { "0" } else { "1" };
+    fixed_codeword.replace_range(index..index + 1, fixed_bit);
+    fixed_codeword
+}
+
+
+fn get_error_location(position: usize) -> ErrorLocation {
+    let codeword_index = position - 1;
+    if power_of_two(position) {
+        ErrorLocation::Parity { codeword_index }
+    } else {
+        // Перед позицией стоит ilog2(position) + 1 контрольных битов
+        let data_index = codeword_index - (position.ilog2() as usize + 1);
+        ErrorLocation::Data { codeword_index, data_index }
+    }
+}
+
+
+/// Decode a Hamming codeword correcting a single error. A syndrome pointing outside
+/// of a shortened codeword can't come from a single error and is uncorrectable.
+pub fn decode(frame: String) -> HammingDecodeResult {
+    let syndrome = get_syndrome(&frame);
+    if syndrome == 0 {
+        return HammingDecodeResult {
+            data: extract_data_bits(&frame),
+            syndrome,
+            error_location: None,
+            status: DecodeStatus::Clean,
+        };
+    }
+    if syndrome > frame.len() {
+        return HammingDecodeResult {
+            data: extract_data_bits(&frame),
+            syndrome,
+            error_location: None,
+            status: DecodeStatus::Uncorrectable,
+        };
+    }
+    HammingDecodeResult {
+        data: extract_data_bits(&flip_bit(&frame, syndrome - 1)),
+        syndrome,
+        error_location: Some(get_error_location(syndrome)),
+        status: DecodeStatus::Corrected,
+    }
+}
+
+
+pub fn decode_and_fix(frame: String) -> String {
+    decode(frame).data
+}
+
+
+fn extract_data_bits(codeword: &str) -> String {
+    codeword.chars()
+        .enumerate()
+        .filter(|(index, _)| !power_of_two(index + 1))
+        .map(|(_, bit)| bit)
+        .collect()
+}
+
+
+fn parity_bit(frame: &str) -> char {
+    if frame.matches('1').count() % 2 == 1 { '1' } else { '0' }
+}
+
+
+/// Hamming codeword followed by an overall parity bit
+pub fn encode_extended(frame: String) -> String {
+    let mut codeword = encode(frame);
+    codeword.push(parity_bit(&codeword));
+    codeword
+}
+
+
+/// Decode an extended Hamming codeword. Single errors are corrected, double errors
+/// are reported as uncorrectable and the data bits are returned as received.
+pub fn decode_extended(frame: String) -> HammingDecodeResult {
+    assert!(frame.len() >= 4, "Extended Hamming codeword must have at least 4 bits, got {}", frame.len());
+    let codeword = &frame[..frame.len() - 1];
+    let parity_failed = parity_bit(&frame) == '1';
+    let mut result = decode(codeword.to_string());
+
+    match (result.syndrome, parity_failed) {
+        (0, false) => {}
+        // Ошибка в самом бите общей чётности
+        (0, true) => {
+            result.error_location = Some(ErrorLocation::OverallParity { codeword_index: codeword.len() });
+            result.status = DecodeStatus::Corrected;
         }
-        if !power_of_two(index + 1){
-            result_frame.push(next_frame_bit);
+        (_, true) => {}
+        // Ненулевой синдром при верной общей чётности - двойная ошибка
+        (_, false) => {
+            result.data = extract_data_bits(codeword);
+            result.error_location = None;
+            result.status = DecodeStatus::Uncorrectable;
         }
-    };
+    }
+    result
+}
 
-    let encoded_frame = encode(result_frame.clone());
-    if power_of_two(control_bit_sum) {
-        for index in 0..frame.len() {
-            if power_of_two(index + 1) {
-                if encoded_frame.as_bytes()[index] != frame.as_bytes()[index] {
-                    return (result_frame, 0);
-                }
-            }
-        }
+
+/// Hamming(n, k) block code for messages longer than one codeword. The message is
+/// split into k-bit blocks, the last one padded with zeros, and one more block
+/// carrying the pad length is appended so the decoder can strip the padding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HammingBlockCode {
+    codeword_bits: usize,
+    data_bits: usize,
+}
+
+impl HammingBlockCode {
+    /// Full-length codes like (7, 4), (15, 11), (31, 26), (63, 57) or shortened ones like (12, 8)
+    pub fn new(codeword_bits: usize, data_bits: usize) -> Self {
+        assert!(data_bits > 0, "Block must carry data bits");
+        assert_eq!(
+            data_bits + get_redundant_bit_count(data_bits as u32),
+            codeword_bits,
+            "Hamming code with {data_bits} data bits needs {} bits per codeword",
+            data_bits + get_redundant_bit_count(data_bits as u32)
+        );
+        HammingBlockCode { codeword_bits, data_bits }
     }
 
-    if control_bit_sum > 0 {
-        let mut x = 1;
-        let mut control_bit_counter = 0;
-        while x < control_bit_sum {
-            x <<= 1;
-            control_bit_counter += 1;
-        }
-        control_bit_sum -= control_bit_counter;
+    pub fn codeword_bits(&self) -> usize { self.codeword_bits }
+
+    pub fn data_bits(&self) -> usize { self.data_bits }
+
+    fn pad_length_block(&self, pad_length: usize) -> String {
+        let binary_pad_length = format!("{:b}", pad_length);
+        assert!(binary_pad_length.len() <= self.data_bits, "Pad length does not fit in a block");
+        "0".repeat(self.data_bits - binary_pad_length.len()) + &binary_pad_length
+    }
+
+    /// Message of any length as whole codewords followed by the pad length codeword
+    pub fn encode_frame(&self, frame: &str) -> String {
+        let pad_length = (self.data_bits - frame.len() % self.data_bits) % self.data_bits;
+        let padded_frame = format!("{}{}{}", frame, "0".repeat(pad_length), self.pad_length_block(pad_length));
+        padded_frame.as_bytes()
+            .chunks(self.data_bits)
+            .map(|block| encode(String::from_utf8(block.to_vec()).unwrap()))
+            .collect()
+    }
+
+    /// Decode every codeword of the frame separately, including the pad length block
+    pub fn decode_blocks(&self, frame: &str) -> Vec<HammingDecodeResult> {
+        assert_eq!(frame.len() % self.codeword_bits, 0, "Frame is not a whole number of codewords");
+        frame.as_bytes()
+            .chunks(self.codeword_bits)
+            .map(|block| decode(String::from_utf8(block.to_vec()).unwrap()))
+            .collect()
     }
 
-    (result_frame, control_bit_sum)
+    /// Message encoded by `encode_frame`, with single errors in every codeword corrected
+    pub fn decode_frame(&self, frame: &str) -> String {
+        assert!(!frame.is_empty(), "Frame has no pad length block");
+        let mut decoded_frame = self.decode_blocks(frame)
+            .into_iter()
+            .map(|result| result.data)
+            .collect::<String>();
+
+        let pad_length_block = decoded_frame.split_off(decoded_frame.len() - self.data_bits);
+        let pad_length = usize::from_str_radix(&pad_length_block, 2).unwrap();
+        decoded_frame.truncate(decoded_frame.len().saturating_sub(pad_length));
+        decoded_frame
+    }
 }
 
+/// A single codeword, without the blocks and pad length of `encode_frame` and `decode_frame`
+impl BlockCode for HammingBlockCode {
+    fn codeword_bits(&self) -> usize { self.codeword_bits }
 
-fn decode_and_fix(frame: String) -> String {
-    let (mut decoded_frame, control_bit_sum) = decode(frame);
-    if control_bit_sum > 0 {
-        if decoded_frame.as_bytes()[control_bit_sum - 1] == '1' as u8 {
-            decoded_frame.replace_range(control_bit_sum-1..control_bit_sum, "0");
-        } else {
-            decoded_frame.replace_range(control_bit_sum-1..control_bit_sum, "1")
-        }
-    };
-    decoded_frame
+    fn data_bits(&self) -> usize { self.data_bits }
+
+    fn encode(&self, data: &str) -> String {
+        assert_eq!(data.len(), self.data_bits, "Code encodes {} bits", self.data_bits);
+        encode(data.to_string())
+    }
+
+    fn decode(&self, codeword: &str) -> BlockDecodeResult {
+        assert_eq!(codeword.len(), self.codeword_bits, "Codeword must have {} bits", self.codeword_bits);
+        let result = decode(codeword.to_string());
+        let corrected_positions = match result.error_location {
+            Some(ErrorLocation::Data { codeword_index, .. } | ErrorLocation::Parity { codeword_index }) => vec![codeword_index],
+            _ => Vec::new(),
+        };
+        BlockDecodeResult { data: result.data, corrected_positions, status: result.status }
+    }
+
+    fn extract_data(&self, codeword: &str) -> String {
+        assert_eq!(codeword.len(), self.codeword_bits, "Codeword must have {} bits", self.codeword_bits);
+        extract_data_bits(codeword)
+    }
 }
 
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_utils::flip_bits;
+
     #[test]
     fn frame_encoded_1() {
         let frame = String::from("101");
@@ -159,22 +343,23 @@ mod tests {
     #[test]
     fn frame_decoded_1() {
         let frame = String::from("11110010001011110001");
-        let (result_frame_2, control_bit_sum) = decode(frame);
-        assert_eq!(control_bit_sum, 0);
+        let result = decode(frame);
+        assert_eq!(result.syndrome, 0);
+        assert_eq!(result.status, DecodeStatus::Clean);
         assert_eq!(
             String::from("100100101110001"),
-            result_frame_2
+            result.data
         )
     }
 
     #[test]
     fn frame_decoded_2(){
         let frame = String::from("000101101110010011000");
-        let (result_frame_2, control_bit_sum) = decode(frame);
-        assert_eq!(control_bit_sum, 0);
+        let result = decode(frame);
+        assert_eq!(result.syndrome, 0);
         assert_eq!(
             String::from("0011111001011000"),
-            result_frame_2
+            result.data
         )
     }
 
@@ -207,4 +392,184 @@ mod tests {
             result_frame
         );
     }
-}
\ No newline at end of file
+
+
+    #[test]
+    fn frame_encoded_extended() {
+        assert_eq!(encode_extended(String::from("101")), "1011010");
+        assert_eq!(encode_extended(String::from("1000001")), "001000010011");
+    }
+
+    #[test]
+    fn extended_frame_decoded_clean() {
+        let result = decode_extended(String::from("0001011011100100110001"));
+        assert_eq!(result.data, "0011111001011000");
+        assert_eq!(result.status, DecodeStatus::Clean);
+    }
+
+    #[test]
+    fn extended_frame_single_errors_corrected() {
+        let codeword = encode_extended(String::from("0011111001011000"));
+        for index in 0..codeword.len() {
+            let result = decode_extended(flip_bits(&codeword, &[index]));
+            assert_eq!(result.data, "0011111001011000");
+            assert_eq!(result.status, DecodeStatus::Corrected);
+        }
+    }
+
+    #[test]
+    fn extended_frame_double_errors_detected() {
+        let codeword = encode_extended(String::from("0011111001011000"));
+        for first in 0..codeword.len() {
+            for second in first + 1..codeword.len() {
+                let result = decode_extended(flip_bits(&codeword, &[first, second]));
+                assert_eq!(result.status, DecodeStatus::Uncorrectable);
+            }
+        }
+    }
+
+    #[test]
+    fn block_codes_sizes() {
+        for (n, k) in [(7, 4), (15, 11), (31, 26), (63, 57), (12, 8)] {
+            let block_code = HammingBlockCode::new(n, k);
+            assert_eq!(block_code.codeword_bits(), n);
+            assert_eq!(block_code.data_bits(), k);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Hamming code with 4 data bits needs 7 bits per codeword")]
+    fn wrong_block_code_rejected() {
+        HammingBlockCode::new(8, 4);
+    }
+
+    #[test]
+    #[should_panic(expected = "needs 7 bits per codeword")]
+    fn codeword_shorter_than_data_rejected() {
+        HammingBlockCode::new(3, 4);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least 4 bits")]
+    fn empty_extended_codeword_rejected() {
+        decode_extended(String::new());
+    }
+
+    #[test]
+    fn long_frame_split_into_blocks() {
+        let block_code = HammingBlockCode::new(7, 4);
+        let encoded_frame = block_code.encode_frame("101100111");
+        // 3 блока данных (последний дополнен тремя нулями) и блок с длиной дополнения
+        assert_eq!(encoded_frame, "0110011100001111100001000011");
+        assert_eq!(block_code.decode_frame(&encoded_frame), "101100111");
+    }
+
+    #[test]
+    fn long_frame_decoded_with_error_in_every_block() {
+        let block_code = HammingBlockCode::new(15, 11);
+        let frame = "10110011100011110000101011101010100101011111000001".repeat(30);
+        let encoded_frame = block_code.encode_frame(&frame);
+        assert_eq!(encoded_frame.len(), (frame.len() / 11 + 2) * 15);
+
+        let error_indexes = (0..encoded_frame.len() / 15)
+            .map(|block| block * 15 + block % 15)
+            .collect::<Vec<usize>>();
+        assert_eq!(block_code.decode_frame(&flip_bits(&encoded_frame, &error_indexes)), frame);
+    }
+
+    #[test]
+    fn frame_of_whole_blocks_not_padded() {
+        let block_code = HammingBlockCode::new(12, 8);
+        let encoded_frame = block_code.encode_frame("1011001110001111");
+        assert_eq!(encoded_frame.len(), 36);
+        assert_eq!(block_code.decode_frame(&encoded_frame), "1011001110001111");
+    }
+
+    #[test]
+    fn data_error_reported_in_both_coordinates() {
+        // Ошибка в 5-й позиции кодового слова - второй бит данных
+        let result = decode(String::from("000111101110010011000"));
+        assert_eq!(result.syndrome, 5);
+        assert_eq!(result.status, DecodeStatus::Corrected);
+        assert_eq!(result.error_location, Some(ErrorLocation::Data { codeword_index: 4, data_index: 1 }));
+        assert_eq!(result.data, "0011111001011000");
+    }
+
+    #[test]
+    fn data_and_parity_errors_reported() {
+        let result = decode(String::from("000101101110000011000"));
+        assert_eq!(result.syndrome, 14);
+        assert_eq!(result.error_location, Some(ErrorLocation::Data { codeword_index: 13, data_index: 9 }));
+
+        let result = decode(flip_bits("000101101110010011000", &[3]));
+        assert_eq!(result.syndrome, 4);
+        assert_eq!(result.error_location, Some(ErrorLocation::Parity { codeword_index: 3 }));
+        assert_eq!(result.data, "0011111001011000");
+    }
+
+    #[test]
+    fn every_single_error_located() {
+        let frame = String::from("0100010000111101");
+        let codeword = encode(frame.clone());
+        let data_indexes = (0..codeword.len()).filter(|index| !power_of_two(index + 1)).collect::<Vec<usize>>();
+        for index in 0..codeword.len() {
+            let result = decode(flip_bits(&codeword, &[index]));
+            assert_eq!(result.data, frame);
+            assert_eq!(result.syndrome, index + 1);
+            let expected_location = match data_indexes.iter().position(|&data_index| data_index == index) {
+                Some(data_index) => ErrorLocation::Data { codeword_index: index, data_index },
+                None => ErrorLocation::Parity { codeword_index: index },
+            };
+            assert_eq!(result.error_location, Some(expected_location));
+        }
+    }
+
+    #[test]
+    fn syndrome_outside_shortened_codeword_uncorrectable() {
+        // Кодовое слово из 6 бит, две ошибки дают синдром 7
+        let result = decode(flip_bits("101101", &[2, 3]));
+        assert_eq!(result.syndrome, 7);
+        assert_eq!(result.status, DecodeStatus::Uncorrectable);
+        assert_eq!(result.error_location, None);
+    }
+
+    #[test]
+    fn overall_parity_error_reported() {
+        let codeword = encode_extended(String::from("101"));
+        let result = decode_extended(flip_bits(&codeword, &[6]));
+        assert_eq!(result.error_location, Some(ErrorLocation::OverallParity { codeword_index: 6 }));
+        assert_eq!(result.status, DecodeStatus::Corrected);
+        assert_eq!(result.data, "101");
+    }
+
+    #[test]
+    fn block_results_reported() {
+        let block_code = HammingBlockCode::new(7, 4);
+        let encoded_frame = block_code.encode_frame("10110011");
+        let results = block_code.decode_blocks(&flip_bits(&encoded_frame, &[9]));
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].status, DecodeStatus::Clean);
+        assert_eq!(results[1].status, DecodeStatus::Corrected);
+        assert_eq!(results[1].error_location, Some(ErrorLocation::Data { codeword_index: 2, data_index: 0 }));
+    }
+
+    #[test]
+    fn single_codeword_through_block_code_interface() {
+        let code: &dyn BlockCode = &HammingBlockCode::new(7, 4);
+        assert_eq!(code.encode("1011"), "0110011");
+        let result = code.decode(&flip_bits("0110011", &[4]));
+        assert_eq!(result.data, "1011");
+        assert_eq!(result.corrected_positions, vec![4]);
+        assert_eq!(result.status, DecodeStatus::Corrected);
+    }
+
+    #[test]
+    fn frame_and_single_codeword_paths_differ() {
+        let code = HammingBlockCode::new(7, 4);
+        // Кадр добавляет кодовое слово с длиной заполнения, одиночное слово - нет
+        assert_eq!(code.encode_frame("1011"), "01100110000000");
+        assert_eq!(BlockCode::encode(&code, "1011"), "0110011");
+        assert_eq!(code.decode_frame("01100110000000"), "1011");
+        assert_eq!(BlockCode::decode(&code, "0110011").data, "1011");
+    }
+}
@@ -0,0 +1,248 @@
+// Код Хэмминга SECDED (72,64) для 64-битных слов, как в ECC памяти.
+// Раскладка совпадает с hamming_code::encode_extended для строки из 64 бит слова
+// (старший бит первым): биты данных занимают позиции 1..71, не являющиеся
+// степенями двойки, контрольный байт хранит биты позиций 1, 2, 4, .., 64 в
+// битах 0..6 и бит общей чётности в бите 7.
+// Таблицы считаются на этапе компиляции: вклад каждого байта слова в синдром
+// и номер бита, на который указывает синдром.
+
+use crate::frame_fix_algos::decode_report::DecodeStatus;
+
+const HAMMING_PARITY_BITS: usize = 7;
+const OVERALL_PARITY_MASK: u8 = 0x80;
+const SYNDROME_MASK: u8 = 0x7f;
+// Значения таблицы синдромов: 0..63 - бит данных, 64 + i - контрольный бит i
+const PARITY_BIT_OFFSET: u8 = 64;
+const INVALID_POSITION: u8 = 0xff;
+
+const DATA_POSITIONS: [u8; 64] = build_data_positions();
+const ENCODE_TABLE: [[u8; 256]; 8] = build_encode_table();
+const SYNDROME_TABLE: [u8; 128] = build_syndrome_table();
+
+
+/// One-based codeword position of every data bit, indexed by bit number (0 is the lowest)
+const fn build_data_positions() -> [u8; 64] {
+    let mut positions = [0u8; 64];
+    let mut position: usize = 1;
+    let mut string_index = 0;
+    while string_index < 64 {
+        if position & (position - 1) != 0 {
+            positions[63 - string_index] = position as u8;
+            string_index += 1;
+        }
+        position += 1;
+    }
+    positions
+}
+
+/// XOR of the positions of the set bits for every byte value at every byte offset
+const fn build_encode_table() -> [[u8; 256]; 8] {
+    let mut table = [[0u8; 256]; 8];
+    let mut byte_index = 0;
+    while byte_index < 8 {
+        let mut value = 0;
+        while value < 256 {
+            let mut syndrome = 0u8;
+            let mut bit = 0;
+            while bit < 8 {
+                if (value >> bit) & 1 == 1 {
+                    syndrome ^= DATA_POSITIONS[byte_index * 8 + bit];
+                }
+                bit += 1;
+            }
+            table[byte_index][value] = syndrome;
+            value += 1;
+        }
+        byte_index += 1;
+    }
+    table
+}
+
+const fn build_syndrome_table() -> [u8; 128] {
+    let mut table = [INVALID_POSITION; 128];
+    let mut bit = 0;
+    while bit < 64 {
+        table[DATA_POSITIONS[bit] as usize] = bit as u8;
+        bit += 1;
+    }
+    let mut parity_bit = 0;
+    while parity_bit < HAMMING_PARITY_BITS {
+        table[1 << parity_bit] = PARITY_BIT_OFFSET + parity_bit as u8;
+        parity_bit += 1;
+    }
+    table
+}
+
+
+fn data_syndrome(data: u64) -> u8 {
+    data.to_le_bytes()
+        .iter()
+        .enumerate()
+        .fold(0, |syndrome, (byte_index, &byte)| syndrome ^ ENCODE_TABLE[byte_index][byte as usize])
+}
+
+
+/// Check byte of a 64-bit word: seven Hamming parity bits and the overall parity bit
+pub fn encode_word(data: u64) -> u8 {
+    let check = data_syndrome(data);
+    let overall_parity = (data.count_ones() + check.count_ones()) % 2;
+    check | ((overall_parity as u8) << 7)
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WordDecodeResult {
+    pub data: u64,
+    pub check: u8,
+    /// Hamming syndrome, equal to the one-based position of a single error
+    pub syndrome: u8,
+    pub status: DecodeStatus,
+}
+
+
+pub fn decode_word(data: u64, check: u8) -> WordDecodeResult {
+    let syndrome = data_syndrome(data) ^ (check & SYNDROME_MASK);
+    let parity_failed = (data.count_ones() + check.count_ones()) % 2 == 1;
+    let mut result = WordDecodeResult { data, check, syndrome, status: DecodeStatus::Clean };
+
+    match (syndrome, parity_failed) {
+        (0, false) => {}
+        (0, true) => {
+            result.check ^= OVERALL_PARITY_MASK;
+            result.status = DecodeStatus::Corrected;
+        }
+        (_, true) => match SYNDROME_TABLE[syndrome as usize] {
+            INVALID_POSITION => result.status = DecodeStatus::Uncorrectable,
+            bit if bit >= PARITY_BIT_OFFSET => {
+                result.check ^= 1 << (bit - PARITY_BIT_OFFSET);
+                result.status = DecodeStatus::Corrected;
+            }
+            bit => {
+                result.data ^= 1 << bit;
+                result.status = DecodeStatus::Corrected;
+            }
+        },
+        (_, false) => result.status = DecodeStatus::Uncorrectable,
+    }
+    result
+}
+
+
+pub fn encode_words(words: &[u64]) -> Vec<u8> {
+    words.iter().map(|&word| encode_word(word)).collect()
+}
+
+
+/// Verify words against their check bytes, correcting single errors in place
+pub fn verify_words(words: &mut [u64], checks: &mut [u8]) -> Vec<DecodeStatus> {
+    assert_eq!(words.len(), checks.len(), "Every word needs a check byte");
+    words.iter_mut()
+        .zip(checks.iter_mut())
+        .map(|(word, check)| {
+            let result = decode_word(*word, *check);
+            *word = result.data;
+            *check = result.check;
+            result.status
+        })
+        .collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame_fix_algos::hamming_code::{decode_extended, encode_extended};
+    use crate::utils::power_of_two;
+
+    const WORDS: [u64; 6] = [
+        0,
+        u64::MAX,
+        1,
+        0x8000_0000_0000_0000,
+        0x0123_4567_89ab_cdef,
+        0xdead_beef_cafe_f00d,
+    ];
+
+    fn codeword_string(data: u64, check: u8) -> String {
+        let data_bits = format!("{:064b}", data);
+        let mut data_chars = data_bits.chars();
+        let mut codeword = String::new();
+        for position in 1..=71usize {
+            if power_of_two(position) {
+                codeword.push(if check & (position as u8) != 0 { '1' } else { '0' });
+            } else {
+                codeword.push(data_chars.next().unwrap());
+            }
+        }
+        codeword.push(if check & OVERALL_PARITY_MASK != 0 { '1' } else { '0' });
+        codeword
+    }
+
+    #[test]
+    fn same_codewords_as_bit_level_code() {
+        for word in WORDS {
+            assert_eq!(
+                codeword_string(word, encode_word(word)),
+                encode_extended(format!("{:064b}", word))
+            );
+        }
+    }
+
+    #[test]
+    fn every_single_error_corrected_like_bit_level_code() {
+        let word = 0x0123_4567_89ab_cdef;
+        let check = encode_word(word);
+        for bit in 0..72 {
+            let (data, corrupted_check) = if bit < 64 {
+                (word ^ (1 << bit), check)
+            } else {
+                (word, check ^ (1 << (bit - 64)))
+            };
+            let result = decode_word(data, corrupted_check);
+            assert_eq!(result.data, word);
+            assert_eq!(result.check, check);
+            assert_eq!(result.status, DecodeStatus::Corrected);
+
+            let bit_level_result = decode_extended(codeword_string(data, corrupted_check));
+            assert_eq!(bit_level_result.syndrome, result.syndrome as usize);
+            assert_eq!(bit_level_result.data, format!("{:064b}", word));
+        }
+    }
+
+    #[test]
+    fn double_errors_detected() {
+        let word = 0xdead_beef_cafe_f00d;
+        let check = encode_word(word);
+        for first in 0..64 {
+            for second in first + 1..72 {
+                let data = word ^ (1 << first) ^ if second < 64 { 1 << second } else { 0 };
+                let corrupted_check = if second < 64 { check } else { check ^ (1 << (second - 64)) };
+                assert_eq!(decode_word(data, corrupted_check).status, DecodeStatus::Uncorrectable);
+            }
+        }
+    }
+
+    #[test]
+    fn words_encoded_and_verified_in_batch() {
+        let mut words = WORDS.to_vec();
+        let mut checks = encode_words(&words);
+        words[1] ^= 1 << 17;
+        checks[4] ^= 0x04;
+        words[5] ^= 0b11;
+
+        let statuses = verify_words(&mut words, &mut checks);
+        assert_eq!(
+            statuses,
+            vec![
+                DecodeStatus::Clean,
+                DecodeStatus::Corrected,
+                DecodeStatus::Clean,
+                DecodeStatus::Clean,
+                DecodeStatus::Corrected,
+                DecodeStatus::Uncorrectable,
+            ]
+        );
+        assert_eq!(&words[..5], &WORDS[..5]);
+        assert_eq!(&checks[..5], &encode_words(&WORDS)[..5]);
+    }
+}
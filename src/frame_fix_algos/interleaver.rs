@@ -0,0 +1,293 @@
+// Перемежители разносят пакет ошибок канала по разным кодовым словам, чтобы
+// каждое слово получило не больше ошибок, чем способен исправить код.
+// Блочный перемежитель записывает символы в таблицу rows x cols по строкам и
+// читает по столбцам: пакет длиной до rows символов попадает в разные строки.
+// Сверточный перемежитель Форни - коммутатор по B ветвям, ветвь i задерживает
+// символ на i * M ячеек, в деперемежителе ветвь i задерживает на (B - 1 - i) * M,
+// так что общая задержка одинакова для всех символов и равна B * (B - 1) * M.
+// Перемежитель QPP (LTE, 36.212) переставляет блок длины K по квадратичному
+// полиному: выходной символ i - входной символ (f1 * i + f2 * i^2) mod K.
+// Символы обобщённые: биты, мягкие решения или Option для стираний.
+
+use std::collections::VecDeque;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockInterleaver {
+    rows: usize,
+    cols: usize,
+}
+
+impl BlockInterleaver {
+    pub fn new(rows: usize, cols: usize) -> Self {
+        assert!(rows > 0 && cols > 0, "Interleaver table can't be empty");
+        BlockInterleaver { rows, cols }
+    }
+
+    pub fn rows(&self) -> usize { self.rows }
+
+    pub fn cols(&self) -> usize { self.cols }
+
+    pub fn block_len(&self) -> usize { self.rows * self.cols }
+
+    /// Write every block row by row and read it column by column
+    pub fn interleave<T: Clone>(&self, symbols: &[T]) -> Vec<T> {
+        self.permute(symbols, self.rows, self.cols)
+    }
+
+    /// Inverse of `interleave`: erasures stay erased, only their positions are restored
+    pub fn deinterleave<T: Clone>(&self, symbols: &[T]) -> Vec<T> {
+        self.permute(symbols, self.cols, self.rows)
+    }
+
+    pub fn interleave_frame(&self, frame: &str) -> String {
+        self.interleave(&frame.chars().collect::<Vec<char>>()).into_iter().collect()
+    }
+
+    pub fn deinterleave_frame(&self, frame: &str) -> String {
+        self.deinterleave(&frame.chars().collect::<Vec<char>>()).into_iter().collect()
+    }
+
+    fn permute<T: Clone>(&self, symbols: &[T], rows: usize, cols: usize) -> Vec<T> {
+        assert_eq!(symbols.len() % self.block_len(), 0, "Frame is not a whole number of {} symbol blocks", self.block_len());
+        symbols.chunks(self.block_len())
+            .flat_map(|block| (0..cols).flat_map(move |col| (0..rows).map(move |row| block[row * cols + col].clone())))
+            .collect()
+    }
+}
+
+
+
+/// Quadratic permutation polynomial interleaver
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QppInterleaver {
+    permutation: Vec<usize>,
+}
+
+impl QppInterleaver {
+    /// Block of `len` symbols with the LTE coefficients f1, f2 (36.212 table 5.1.3-3)
+    pub fn new(len: usize, f1: usize, f2: usize) -> Self {
+        assert!(len > 0, "Interleaver block can't be empty");
+        let permutation = (0..len)
+            .map(|index| (f1 * index + f2 * index % len * index) % len)
+            .collect::<Vec<usize>>();
+        let mut used = vec![false; len];
+        for &position in &permutation {
+            assert!(!used[position], "f1 = {f1}, f2 = {f2} is not a permutation polynomial for length {len}");
+            used[position] = true;
+        }
+        QppInterleaver { permutation }
+    }
+
+    pub fn len(&self) -> usize { self.permutation.len() }
+
+    pub fn is_empty(&self) -> bool { self.permutation.is_empty() }
+
+    /// Input index of every output symbol
+    pub fn permutation(&self) -> &[usize] { &self.permutation }
+
+    pub fn interleave<T: Clone>(&self, symbols: &[T]) -> Vec<T> {
+        assert_eq!(symbols.len(), self.len(), "QPP interleaver block has {} symbols", self.len());
+        self.permutation.iter().map(|&index| symbols[index].clone()).collect()
+    }
+
+    pub fn deinterleave<T: Clone>(&self, symbols: &[T]) -> Vec<T> {
+        assert_eq!(symbols.len(), self.len(), "QPP interleaver block has {} symbols", self.len());
+        let mut deinterleaved = symbols.to_vec();
+        for (&index, symbol) in self.permutation.iter().zip(symbols) {
+            deinterleaved[index] = symbol.clone();
+        }
+        deinterleaved
+    }
+}
+
+
+/// Delay lines of a convolutional interleaver, served by a commutator
+struct DelayLines<T> {
+    lines: Vec<VecDeque<Option<T>>>,
+    commutator: usize,
+}
+
+impl<T> DelayLines<T> {
+    fn new(delays: impl Iterator<Item = usize>) -> Self {
+        let lines = delays.map(|delay| (0..delay).map(|_| None).collect()).collect();
+        DelayLines { lines, commutator: 0 }
+    }
+
+    fn push(&mut self, symbol: Option<T>) -> Option<T> {
+        let branch = self.commutator;
+        self.commutator = (self.commutator + 1) % self.lines.len();
+        let line = &mut self.lines[branch];
+        if line.is_empty() {
+            return symbol;
+        }
+        line.push_back(symbol);
+        line.pop_front().unwrap()
+    }
+}
+
+
+pub struct ConvolutionalInterleaver<T> {
+    delay_lines: DelayLines<T>,
+    cell_depth: usize,
+}
+
+impl<T> ConvolutionalInterleaver<T> {
+    /// Branch i delays its symbols by i * cell_depth commutator turns
+    pub fn new(branches: usize, cell_depth: usize) -> Self {
+        assert!(branches > 0, "Interleaver needs at least one branch");
+        ConvolutionalInterleaver {
+            delay_lines: DelayLines::new((0..branches).map(|branch| branch * cell_depth)),
+            cell_depth,
+        }
+    }
+
+    /// Delay between a symbol entering the interleaver and leaving the deinterleaver
+    pub fn latency(&self) -> usize {
+        let branches = self.delay_lines.lines.len();
+        branches * (branches - 1) * self.cell_depth
+    }
+
+    /// Interleave one symbol. `None` is returned while the delay lines are still filling
+    pub fn push(&mut self, symbol: T) -> Option<T> {
+        self.delay_lines.push(Some(symbol))
+    }
+
+    pub fn process(&mut self, symbols: impl IntoIterator<Item = T>) -> Vec<Option<T>> {
+        symbols.into_iter().map(|symbol| self.push(symbol)).collect()
+    }
+}
+
+
+pub struct ConvolutionalDeinterleaver<T> {
+    delay_lines: DelayLines<T>,
+}
+
+impl<T> ConvolutionalDeinterleaver<T> {
+    /// Deinterleaver matching `ConvolutionalInterleaver::new(branches, cell_depth)`
+    pub fn new(branches: usize, cell_depth: usize) -> Self {
+        assert!(branches > 0, "Deinterleaver needs at least one branch");
+        ConvolutionalDeinterleaver {
+            delay_lines: DelayLines::new((0..branches).rev().map(|branch| branch * cell_depth)),
+        }
+    }
+
+    /// Deinterleave one received symbol, `None` marks an erasure or an unfilled cell
+    pub fn push(&mut self, symbol: Option<T>) -> Option<T> {
+        self.delay_lines.push(symbol)
+    }
+
+    pub fn process(&mut self, symbols: impl IntoIterator<Item = Option<T>>) -> Vec<Option<T>> {
+        symbols.into_iter().map(|symbol| self.push(symbol)).collect()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame_fix_algos::hamming_code::HammingBlockCode;
+    use crate::test_utils::flip_bits;
+
+    #[test]
+    fn block_interleaver_reads_columns() {
+        let interleaver = BlockInterleaver::new(3, 4);
+        let symbols = (0..24).collect::<Vec<usize>>();
+        let interleaved = interleaver.interleave(&symbols);
+        assert_eq!(&interleaved[..12], &[0, 4, 8, 1, 5, 9, 2, 6, 10, 3, 7, 11]);
+        assert_eq!(interleaved[12], 12);
+        assert_eq!(interleaver.deinterleave(&interleaved), symbols);
+
+        assert_eq!(interleaver.interleave_frame("111100000000"), "100100100100");
+        assert_eq!(interleaver.deinterleave_frame("100100100100"), "111100000000");
+    }
+
+    #[test]
+    fn burst_spread_over_hamming_codewords() {
+        let code = HammingBlockCode::new(7, 4);
+        let frame = "10110010111000011101";
+        let codewords = code.encode_frame(frame);
+        assert_eq!(codewords.len(), 42);
+
+        // Без перемежения пакет из 5 бит ломает код
+        assert_ne!(code.decode_frame(&flip_bits(&codewords, 10..15)), frame);
+
+        let interleaver = BlockInterleaver::new(6, 7);
+        let received = flip_bits(&interleaver.interleave_frame(&codewords), 10..15);
+        assert_eq!(code.decode_frame(&interleaver.deinterleave_frame(&received)), frame);
+    }
+
+    #[test]
+    fn block_deinterleaver_restores_erasure_positions() {
+        let interleaver = BlockInterleaver::new(4, 2);
+        let mut received = interleaver.interleave(&(0..8).map(Some).collect::<Vec<Option<usize>>>());
+        received[2] = None;
+        received[3] = None;
+        let deinterleaved = interleaver.deinterleave(&received);
+        assert_eq!(deinterleaved, vec![Some(0), Some(1), Some(2), Some(3), None, Some(5), None, Some(7)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Frame is not a whole number of 12 symbol blocks")]
+    fn partial_block_rejected() {
+        BlockInterleaver::new(3, 4).interleave_frame("10110");
+    }
+
+    #[test]
+    fn qpp_interleaver_for_lte_block_of_40() {
+        let interleaver = QppInterleaver::new(40, 3, 10);
+        assert_eq!(&interleaver.permutation()[..4], &[0, 13, 6, 19]);
+        let symbols = (100..140).collect::<Vec<usize>>();
+        let interleaved = interleaver.interleave(&symbols);
+        assert_eq!(interleaved[1], 113);
+        assert_eq!(interleaver.deinterleave(&interleaved), symbols);
+    }
+
+    #[test]
+    #[should_panic(expected = "f1 = 2, f2 = 10 is not a permutation polynomial for length 40")]
+    fn qpp_coefficients_must_give_permutation() {
+        QppInterleaver::new(40, 2, 10);
+    }
+
+    #[test]
+    fn convolutional_interleaver_delays_by_branch() {
+        let mut interleaver = ConvolutionalInterleaver::new(3, 1);
+        let output = interleaver.process(0..9);
+        assert_eq!(
+            output,
+            vec![Some(0), None, None, Some(3), Some(1), None, Some(6), Some(4), Some(2)]
+        );
+    }
+
+    #[test]
+    fn convolutional_pair_restores_order_after_latency() {
+        let mut interleaver = ConvolutionalInterleaver::new(12, 17);
+        let mut deinterleaver = ConvolutionalDeinterleaver::new(12, 17);
+        let latency = interleaver.latency();
+        assert_eq!(latency, 12 * 11 * 17);
+
+        let symbols = (0..latency + 500).collect::<Vec<usize>>();
+        let channel = interleaver.process(symbols.iter().copied());
+        let output = deinterleaver.process(channel);
+        assert!(output[..latency].iter().all(Option::is_none));
+        assert_eq!(output[latency..].iter().map(|symbol| symbol.unwrap()).collect::<Vec<usize>>(), symbols[..500]);
+    }
+
+    #[test]
+    fn convolutional_burst_of_erasures_spread() {
+        let (branches, cell_depth) = (4, 2);
+        let mut interleaver = ConvolutionalInterleaver::new(branches, cell_depth);
+        let mut deinterleaver = ConvolutionalDeinterleaver::new(branches, cell_depth);
+        let latency = interleaver.latency();
+
+        let mut channel = interleaver.process(0..200usize);
+        for symbol in &mut channel[100..104] {
+            *symbol = None;
+        }
+        let output = deinterleaver.process(channel);
+        let erased = (latency..200).filter(|&index| output[index].is_none()).collect::<Vec<usize>>();
+        assert_eq!(erased.len(), 4);
+        // Соседние символы пакета попадают на расстояние B * M - 1
+        assert!(erased.windows(2).all(|pair| pair[1] - pair[0] == branches * cell_depth - 1));
+    }
+}
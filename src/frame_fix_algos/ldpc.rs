@@ -0,0 +1,463 @@
+// Коды с малой плотностью проверок на чётность (LDPC). Код задаётся разреженной
+// проверочной матрицей H: для каждой проверки хранится список участвующих бит.
+// Квазициклическая матрица (как в 802.11n) задаётся базовой матрицей сдвигов:
+// -1 - нулевой блок z x z, s >= 0 - единичная матрица, циклически сдвинутая на s
+// вправо. Готовая матрица из 802.11n - код n = 648, R = 1/2, z = 27.
+// Кодер приводит H к ступенчатому виду над GF(2), выбирая ведущие столбцы справа
+// налево: проверочные биты ставятся на ведущие столбцы, данные - на остальные,
+// поэтому при обратимой правой части H код систематический (данные в начале).
+// Декодер - распространение доверия (belief propagation) по графу Таннера с
+// параллельным расписанием: sum-product (tanh) или min-sum с масштабированием.
+// Входные LLR положительны для 0, как в декодере Витерби. Декодирование
+// останавливается, как только жёсткое решение даёт нулевой синдром.
+
+use crate::frame_fix_algos::block_code::BlockCode;
+use crate::frame_fix_algos::decode_report::{BlockDecodeResult, DecodeStatus};
+use crate::frame_fix_algos::viterbi::SoftSymbols;
+use crate::utils::{bits_from_str, bits_to_string};
+
+// Ограничение tanh, чтобы atanh не уходил в бесконечность на уверенных сообщениях
+const MAX_TANH: f64 = 0.999_999_999_999;
+
+// Число итераций при декодировании жёстких решений через общий интерфейс блочных кодов
+const HARD_DECISION_ITERATIONS: usize = 50;
+
+// IEEE 802.11n, n = 648, R = 1/2, z = 27: 12 столбцов данных, столбец веса 3 и
+// двойная диагональ в проверочной части
+const IEEE_802_11N_648_R1_2: [[i32; 24]; 12] = [
+    [0, -1, -1, -1, 0, 0, -1, -1, 0, -1, -1, 0, 1, 0, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [22, 0, -1, -1, 17, -1, 0, 0, 12, -1, -1, -1, -1, 0, 0, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [6, -1, 0, -1, 10, -1, -1, -1, 24, -1, 0, -1, -1, -1, 0, 0, -1, -1, -1, -1, -1, -1, -1, -1],
+    [2, -1, -1, 0, 20, -1, -1, -1, 25, 0, -1, -1, -1, -1, -1, 0, 0, -1, -1, -1, -1, -1, -1, -1],
+    [23, -1, -1, -1, 3, -1, -1, -1, 0, -1, 9, 11, -1, -1, -1, -1, 0, 0, -1, -1, -1, -1, -1, -1],
+    [24, -1, 23, 1, 17, -1, 3, -1, 10, -1, -1, -1, -1, -1, -1, -1, -1, 0, 0, -1, -1, -1, -1, -1],
+    [25, -1, -1, -1, 8, -1, -1, -1, 7, 18, -1, -1, 0, -1, -1, -1, -1, -1, 0, 0, -1, -1, -1, -1],
+    [13, 24, -1, -1, 0, -1, 8, -1, 6, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 0, 0, -1, -1, -1],
+    [7, 20, -1, 16, 22, 10, -1, -1, 23, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 0, 0, -1, -1],
+    [11, -1, -1, -1, 19, -1, -1, -1, 13, -1, 3, 17, -1, -1, -1, -1, -1, -1, -1, -1, -1, 0, 0, -1],
+    [25, -1, 8, -1, 23, 18, -1, 14, 9, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 0, 0],
+    [3, -1, -1, -1, 16, -1, -1, 2, 25, 5, -1, -1, 1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 0],
+];
+const IEEE_802_11N_648_LIFTING_SIZE: usize = 27;
+
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LdpcAlgorithm {
+    /// Exact check node update through tanh and atanh
+    SumProduct,
+    /// Minimum magnitude of the other inputs, multiplied by `scaling` (1.0 for plain min-sum)
+    MinSum { scaling: f64 },
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LdpcDecodeResult {
+    pub data: String,
+    /// Zero-based codeword positions where the decision differs from the channel hard decision
+    pub corrected_positions: Vec<usize>,
+    /// Iterations run, 0 if the channel decision was already a codeword
+    pub iterations: usize,
+    pub status: DecodeStatus,
+}
+
+
+pub struct LdpcCode {
+    codeword_bits: usize,
+    check_rows: Vec<Vec<usize>>,
+    variable_checks: Vec<Vec<usize>>,
+    data_positions: Vec<usize>,
+    parity_positions: Vec<usize>,
+    /// Data indexes XORed into every parity bit, aligned with `parity_positions`
+    parity_equations: Vec<Vec<usize>>,
+}
+
+impl LdpcCode {
+    /// Code from a sparse parity-check matrix: for every check the codeword positions it covers
+    pub fn from_rows(codeword_bits: usize, check_rows: Vec<Vec<usize>>) -> Self {
+        let mut variable_checks = vec![Vec::new(); codeword_bits];
+        for (check, row) in check_rows.iter().enumerate() {
+            for &variable in row {
+                assert!(variable < codeword_bits, "Check {check} covers bit {variable} outside of the codeword");
+                variable_checks[variable].push(check);
+            }
+        }
+
+        let (parity_positions, parity_equations, data_positions) = build_encoder(codeword_bits, &check_rows);
+        assert!(!data_positions.is_empty(), "Parity-check matrix leaves no data bits");
+        LdpcCode { codeword_bits, check_rows, variable_checks, data_positions, parity_positions, parity_equations }
+    }
+
+    /// Code from dense rows of H like "1101000"
+    pub fn from_dense(rows: &[&str]) -> Self {
+        assert!(!rows.is_empty(), "Parity-check matrix has no rows");
+        let codeword_bits = rows[0].len();
+        let check_rows = rows.iter().map(|row| {
+            assert_eq!(row.len(), codeword_bits, "All rows of H must have the same length");
+            row.char_indices().filter(|&(_, bit)| bit == '1').map(|(index, _)| index).collect()
+        }).collect();
+        Self::from_rows(codeword_bits, check_rows)
+    }
+
+    /// Quasi-cyclic code from a base matrix of shifts and the lifting size z
+    pub fn from_base_matrix(base_matrix: &[Vec<i32>], lifting_size: usize) -> Self {
+        assert!(lifting_size > 0, "Lifting size must be positive");
+        let base_cols = base_matrix[0].len();
+        let mut check_rows = Vec::with_capacity(base_matrix.len() * lifting_size);
+        for base_row in base_matrix {
+            assert_eq!(base_row.len(), base_cols, "All rows of the base matrix must have the same length");
+            for row in 0..lifting_size {
+                check_rows.push(
+                    base_row.iter()
+                        .enumerate()
+                        .filter(|&(_, &shift)| shift >= 0)
+                        .map(|(base_col, &shift)| base_col * lifting_size + (row + shift as usize) % lifting_size)
+                        .collect()
+                );
+            }
+        }
+        Self::from_rows(base_cols * lifting_size, check_rows)
+    }
+
+    /// IEEE 802.11n rate 1/2 code with 648-bit codewords
+    pub fn ieee_802_11n_648_rate_1_2() -> Self {
+        let base_matrix = IEEE_802_11N_648_R1_2.iter().map(|row| row.to_vec()).collect::<Vec<Vec<i32>>>();
+        Self::from_base_matrix(&base_matrix, IEEE_802_11N_648_LIFTING_SIZE)
+    }
+
+    pub fn codeword_bits(&self) -> usize { self.codeword_bits }
+
+    pub fn data_bits(&self) -> usize { self.data_positions.len() }
+
+    pub fn checks_count(&self) -> usize { self.check_rows.len() }
+
+    /// Codeword positions carrying data bits, in data order
+    pub fn data_positions(&self) -> &[usize] { &self.data_positions }
+
+    pub fn encode(&self, data: &str) -> String {
+        assert_eq!(data.len(), self.data_bits(), "LDPC code encodes {} bits", self.data_bits());
+        let data_bits = bits_from_str(data);
+        let mut codeword = vec![0u8; self.codeword_bits];
+        for (&position, &bit) in self.data_positions.iter().zip(&data_bits) {
+            codeword[position] = bit;
+        }
+        for (&position, equation) in self.parity_positions.iter().zip(&self.parity_equations) {
+            codeword[position] = equation.iter().fold(0, |parity, &data_index| parity ^ data_bits[data_index]);
+        }
+        bits_to_string(&codeword)
+    }
+
+    pub fn is_codeword(&self, codeword: &str) -> bool {
+        assert_eq!(codeword.len(), self.codeword_bits, "LDPC codeword must have {} bits", self.codeword_bits);
+        self.syndrome_is_zero(&bits_from_str(codeword))
+    }
+
+    fn syndrome_is_zero(&self, bits: &[u8]) -> bool {
+        self.check_rows.iter().all(|row| row.iter().fold(0, |parity, &variable| parity ^ bits[variable]) == 0)
+    }
+
+    /// Iterative belief-propagation decoding of soft channel symbols
+    pub fn decode(&self, symbols: &SoftSymbols, algorithm: LdpcAlgorithm, max_iterations: usize) -> LdpcDecodeResult {
+        let channel_llrs = symbols.to_llrs();
+        assert_eq!(channel_llrs.len(), self.codeword_bits, "LDPC codeword must have {} symbols", self.codeword_bits);
+        let channel_bits = hard_decision(&channel_llrs);
+
+        let mut check_messages = self.check_rows.iter().map(|row| vec![0.0; row.len()]).collect::<Vec<Vec<f64>>>();
+        let mut totals = channel_llrs.clone();
+        let mut bits = channel_bits.clone();
+        let mut iterations = 0;
+        while !self.syndrome_is_zero(&bits) && iterations < max_iterations {
+            for (row, messages) in self.check_rows.iter().zip(check_messages.iter_mut()) {
+                // Сообщение бита в проверку - всё, что известно о бите, кроме самой проверки
+                let inputs = row.iter()
+                    .zip(messages.iter())
+                    .map(|(&variable, &message)| totals[variable] - message)
+                    .collect::<Vec<f64>>();
+                for (index, message) in messages.iter_mut().enumerate() {
+                    *message = check_update(&inputs, index, algorithm);
+                }
+            }
+
+            totals.copy_from_slice(&channel_llrs);
+            for (row, messages) in self.check_rows.iter().zip(&check_messages) {
+                for (&variable, &message) in row.iter().zip(messages) {
+                    totals[variable] += message;
+                }
+            }
+            bits = hard_decision(&totals);
+            iterations += 1;
+        }
+
+        let status = if iterations == 0 {
+            DecodeStatus::Clean
+        } else if self.syndrome_is_zero(&bits) {
+            DecodeStatus::Corrected
+        } else {
+            DecodeStatus::Uncorrectable
+        };
+        let corrected_positions = (0..self.codeword_bits)
+            .filter(|&position| bits[position] != channel_bits[position])
+            .collect();
+        let data = self.data_positions.iter().map(|&position| bits[position]).collect::<Vec<u8>>();
+        LdpcDecodeResult { data: bits_to_string(&data), corrected_positions, iterations, status }
+    }
+
+    /// Number of checks every codeword bit takes part in
+    pub fn variable_degrees(&self) -> Vec<usize> {
+        self.variable_checks.iter().map(Vec::len).collect()
+    }
+}
+
+impl BlockCode for LdpcCode {
+    fn codeword_bits(&self) -> usize { self.codeword_bits }
+
+    fn data_bits(&self) -> usize { self.data_positions.len() }
+
+    fn encode(&self, data: &str) -> String { LdpcCode::encode(self, data) }
+
+    /// Min-sum decoding of hard decisions, which is what a hard decision input reduces to
+    fn decode(&self, codeword: &str) -> BlockDecodeResult {
+        let llrs = codeword.bytes().map(|bit| if bit == b'0' { 1.0 } else { -1.0 }).collect();
+        let result = LdpcCode::decode(self, &SoftSymbols::Llr(llrs), LdpcAlgorithm::MinSum { scaling: 1.0 }, HARD_DECISION_ITERATIONS);
+        BlockDecodeResult { data: result.data, corrected_positions: result.corrected_positions, status: result.status }
+    }
+
+    fn extract_data(&self, codeword: &str) -> String {
+        assert_eq!(codeword.len(), self.codeword_bits, "LDPC codeword must have {} bits", self.codeword_bits);
+        self.data_positions.iter().map(|&position| &codeword[position..position + 1]).collect()
+    }
+}
+
+
+fn hard_decision(llrs: &[f64]) -> Vec<u8> {
+    llrs.iter().map(|&llr| if llr < 0.0 { 1 } else { 0 }).collect()
+}
+
+
+/// Check to variable message for input `skip`, computed from all other inputs of the check
+fn check_update(inputs: &[f64], skip: usize, algorithm: LdpcAlgorithm) -> f64 {
+    let others = inputs.iter().enumerate().filter(|&(index, _)| index != skip).map(|(_, &llr)| llr);
+    match algorithm {
+        LdpcAlgorithm::SumProduct => {
+            let product = others.fold(1.0, |product, llr| product * (llr / 2.0).tanh());
+            2.0 * product.clamp(-MAX_TANH, MAX_TANH).atanh()
+        }
+        LdpcAlgorithm::MinSum { scaling } => {
+            let (sign, magnitude) = others.fold((1.0, f64::INFINITY), |(sign, magnitude), llr| {
+                (if llr < 0.0 { -sign } else { sign }, magnitude.min(llr.abs()))
+            });
+            scaling * sign * magnitude
+        }
+    }
+}
+
+
+/// Gaussian elimination of H over GF(2) with pivots taken from the rightmost columns.
+/// Returns parity positions, their equations over data indexes and data positions.
+fn build_encoder(codeword_bits: usize, check_rows: &[Vec<usize>]) -> (Vec<usize>, Vec<Vec<usize>>, Vec<usize>) {
+    let words = codeword_bits.div_ceil(64);
+    let mut rows = check_rows.iter().map(|row| {
+        let mut bitset = vec![0u64; words];
+        for &variable in row {
+            bitset[variable / 64] ^= 1 << (variable % 64);
+        }
+        bitset
+    }).collect::<Vec<Vec<u64>>>();
+    let is_set = |row: &[u64], column: usize| (row[column / 64] >> (column % 64)) & 1 == 1;
+
+    let mut pivots = Vec::new();
+    let mut rank = 0;
+    for column in (0..codeword_bits).rev() {
+        let Some(pivot_row) = (rank..rows.len()).find(|&row| is_set(&rows[row], column)) else {
+            continue;
+        };
+        rows.swap(rank, pivot_row);
+        let pivot = rows[rank].clone();
+        for (index, row) in rows.iter_mut().enumerate() {
+            if index != rank && is_set(row, column) {
+                for (word, pivot_word) in row.iter_mut().zip(&pivot) {
+                    *word ^= pivot_word;
+                }
+            }
+        }
+        pivots.push(column);
+        rank += 1;
+    }
+
+    let mut is_parity = vec![false; codeword_bits];
+    for &column in &pivots {
+        is_parity[column] = true;
+    }
+    let data_positions = (0..codeword_bits).filter(|&column| !is_parity[column]).collect::<Vec<usize>>();
+    // Строка с ведущим столбцом p: бит p = XOR битов данных, отмеченных в строке
+    let parity_equations = pivots.iter().enumerate().map(|(row, _)| {
+        data_positions.iter()
+            .enumerate()
+            .filter(|&(_, &column)| is_set(&rows[row], column))
+            .map(|(data_index, _)| data_index)
+            .collect()
+    }).collect();
+    (pivots, parity_equations, data_positions)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame_fix_algos::prbs::{PrbsGenerator, PrbsPattern};
+    use crate::test_utils::{bpsk_llrs, flip_bits};
+
+    const HAMMING_7_4: [&str; 3] = ["1010101", "0110011", "0001111"];
+
+    // Небольшая базовая матрица 4 x 8 с произвольными сдвигами: слева данные,
+    // справа столбец веса 3 и двойная диагональ
+    fn qc_code() -> LdpcCode {
+        let base_matrix = vec![
+            vec![3, 17, 8, 21, 1, 0, -1, -1],
+            vec![12, -1, 5, 14, -1, 0, 0, -1],
+            vec![-1, 9, 20, 2, 0, -1, 0, 0],
+            vec![7, 23, -1, 11, 1, -1, -1, 0],
+        ];
+        LdpcCode::from_base_matrix(&base_matrix, 24)
+    }
+
+    /// BPSK LLRs of a codeword with sign errors at `errors`
+    fn noisy_llrs(codeword: &str, errors: &[usize]) -> Vec<f64> {
+        bpsk_llrs(codeword, |index| {
+            let magnitude = 2.0 + (index * 37 % 11) as f64 / 5.0;
+            if errors.contains(&index) { -0.6 * magnitude } else { magnitude }
+        })
+    }
+
+    #[test]
+    fn hamming_matrix_encoded() {
+        let code = LdpcCode::from_dense(&HAMMING_7_4);
+        assert_eq!((code.codeword_bits(), code.data_bits()), (7, 4));
+        assert_eq!(code.data_positions(), &[0, 1, 2, 3]);
+        for value in 0..16 {
+            let data = format!("{:04b}", value);
+            let codeword = code.encode(&data);
+            assert!(code.is_codeword(&codeword));
+            assert_eq!(code.data_positions().iter().map(|&index| &codeword[index..index + 1]).collect::<String>(), data);
+        }
+        assert!(!code.is_codeword("1000000"));
+    }
+
+    #[test]
+    fn hard_decisions_decoded_through_block_code_interface() {
+        let code: &dyn BlockCode = &LdpcCode::ieee_802_11n_648_rate_1_2();
+        let data = PrbsGenerator::new(PrbsPattern::Prbs9).generate(324);
+        let codeword = code.encode(&data);
+        let result = code.decode(&flip_bits(&codeword, &[17, 250, 401, 630]));
+        assert_eq!(result.data, data);
+        assert_eq!(result.corrected_positions, vec![17, 250, 401, 630]);
+        assert_eq!(result.status, DecodeStatus::Corrected);
+    }
+
+    #[test]
+    fn redundant_checks_do_not_reduce_data_bits() {
+        let code = LdpcCode::from_dense(&["1010101", "0110011", "0001111", "1101001"]);
+        assert_eq!(code.data_bits(), 4);
+        assert!(code.is_codeword(&code.encode("1011")));
+    }
+
+    #[test]
+    fn quasi_cyclic_code_is_systematic() {
+        let code = qc_code();
+        assert_eq!((code.codeword_bits(), code.checks_count()), (192, 96));
+        assert_eq!(code.data_bits(), 96);
+        assert_eq!(code.data_positions(), &(0..96).collect::<Vec<usize>>()[..]);
+        assert_eq!(&code.variable_degrees()[..24], &[3; 24]);
+        assert_eq!(&code.variable_degrees()[96..120], &[3; 24]);
+
+        let data = PrbsGenerator::new(PrbsPattern::Prbs9).generate(96);
+        let codeword = code.encode(&data);
+        assert!(code.is_codeword(&codeword));
+        assert_eq!(&codeword[..96], data);
+    }
+
+    #[test]
+    fn ieee_802_11n_code_has_full_rank() {
+        let code = LdpcCode::ieee_802_11n_648_rate_1_2();
+        assert_eq!((code.codeword_bits(), code.checks_count()), (648, 324));
+        // Ранг H равен числу проверок: данных ровно n - 324
+        assert_eq!(code.data_bits(), 324);
+        assert_eq!(code.data_positions(), &(0..324).collect::<Vec<usize>>()[..]);
+        assert_eq!(&code.variable_degrees()[324..351], &[3; 27]);
+        assert_eq!(&code.variable_degrees()[351..], &[2; 297]);
+
+        let mut generator = PrbsGenerator::new(PrbsPattern::Prbs23);
+        for _ in 0..4 {
+            let data = generator.generate(324);
+            let codeword = code.encode(&data);
+            assert!(code.is_codeword(&codeword));
+            assert_eq!(&codeword[..324], data);
+        }
+    }
+
+    #[test]
+    fn ieee_802_11n_code_corrects_errors() {
+        let code = LdpcCode::ieee_802_11n_648_rate_1_2();
+        let data = PrbsGenerator::new(PrbsPattern::Prbs15).generate(324);
+        let errors = (0..30).map(|index| index * 21 + 4).collect::<Vec<usize>>();
+        let symbols = SoftSymbols::Llr(noisy_llrs(&code.encode(&data), &errors));
+        let result = code.decode(&symbols, LdpcAlgorithm::MinSum { scaling: 0.75 }, 50);
+        assert_eq!(result.data, data);
+        assert_eq!(result.corrected_positions, errors);
+    }
+
+    #[test]
+    fn clean_frame_stops_before_iterating() {
+        let code = qc_code();
+        let data = PrbsGenerator::new(PrbsPattern::Prbs7).generate(96);
+        let symbols = SoftSymbols::Llr(noisy_llrs(&code.encode(&data), &[]));
+        let result = code.decode(&symbols, LdpcAlgorithm::SumProduct, 50);
+        assert_eq!(result.data, data);
+        assert_eq!(result.iterations, 0);
+        assert_eq!(result.status, DecodeStatus::Clean);
+    }
+
+    #[test]
+    fn sum_product_and_min_sum_correct_errors() {
+        let code = qc_code();
+        let data = PrbsGenerator::new(PrbsPattern::Prbs15).generate(96);
+        let codeword = code.encode(&data);
+        let symbols = SoftSymbols::Llr(noisy_llrs(&codeword, &[5, 30, 61, 77, 102, 140, 151, 188]));
+
+        for algorithm in [LdpcAlgorithm::SumProduct, LdpcAlgorithm::MinSum { scaling: 0.75 }, LdpcAlgorithm::MinSum { scaling: 1.0 }] {
+            let result = code.decode(&symbols, algorithm, 50);
+            assert_eq!(result.data, data);
+            assert_eq!(result.status, DecodeStatus::Corrected);
+            assert_eq!(result.corrected_positions.len(), 8);
+            assert!(result.iterations > 0 && result.iterations < 50);
+        }
+    }
+
+    #[test]
+    fn quantized_symbols_decoded() {
+        let code = LdpcCode::from_dense(&HAMMING_7_4);
+        let codeword = code.encode("0110");
+        let mut values = codeword.bytes().map(|bit| if bit == b'0' { 0 } else { 7 }).collect::<Vec<u8>>();
+        // Слабая ошибка в первом бите
+        values[0] = 4;
+        let result = code.decode(&SoftSymbols::Quantized { bits: 3, values }, LdpcAlgorithm::SumProduct, 10);
+        assert_eq!(result.data, "0110");
+        assert_eq!(result.corrected_positions, vec![0]);
+    }
+
+    #[test]
+    fn hopeless_frame_reported_uncorrectable() {
+        let code = qc_code();
+        let codeword = code.encode(&"0".repeat(96));
+        let errors = (0..60).map(|index| index * 3 + 1).collect::<Vec<usize>>();
+        let symbols = SoftSymbols::Llr(noisy_llrs(&codeword, &errors));
+        let result = code.decode(&symbols, LdpcAlgorithm::MinSum { scaling: 0.75 }, 20);
+        assert_eq!(result.status, DecodeStatus::Uncorrectable);
+        assert_eq!(result.iterations, 20);
+    }
+
+    #[test]
+    #[should_panic(expected = "Check 1 covers bit 4 outside of the codeword")]
+    fn check_outside_of_codeword_rejected() {
+        LdpcCode::from_rows(4, vec![vec![0, 1], vec![2, 4]]);
+    }
+}
@@ -1,4 +1,22 @@
+pub mod bch;
+pub mod block_code;
+pub mod ccsds;
+pub mod code_analysis;
+pub mod cyclic_code;
+pub mod decode_report;
+pub mod golay;
 pub mod hamming_code;
-mod nasa_convo_code;
-mod steps;
-mod state_machine;
\ No newline at end of file
+pub mod hamming_word;
+pub mod interleaver;
+pub mod ldpc;
+pub mod nasa_convo_code;
+pub mod polar;
+pub mod state_machine;
+pub mod prbs;
+pub mod product_code;
+pub mod puncture;
+pub mod reed_muller;
+pub mod reed_solomon;
+pub mod trellis;
+pub mod turbo;
+pub mod viterbi;
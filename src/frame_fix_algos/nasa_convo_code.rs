@@ -1,16 +1,30 @@
 use std::collections::HashMap;
 
-use crate::utils::{binary_to_decimal};
-use crate::frame_fix_algos::steps::{StateStep};
+use crate::utils::{bits_from_str, bits_to_string};
 use crate::frame_fix_algos::state_machine::BitSM;
+use crate::frame_fix_algos::puncture::{depuncture, puncture, CodeRate};
+use crate::frame_fix_algos::trellis::Trellis;
+use crate::frame_fix_algos::viterbi::{
+    decode_hard,
+    decode_hard_terminated,
+    decode_soft as viterbi_decode_soft,
+    decode_soft_terminated as viterbi_decode_soft_terminated,
+    BranchMetric,
+    SoftSymbols,
+    Termination
+};
 
 
-fn encode(mut frame: String, bit_sm: &mut BitSM, states_map: &HashMap<String, HashMap<String, String>>) -> String {
-    let mut result_frame = String::new();
+pub fn encode(mut frame: String, bit_sm: &mut BitSM, states_map: &HashMap<String, HashMap<String, String>>) -> String {
     if frame.len() < bit_sm.get_register_size() {
         frame.push_str("0".repeat(bit_sm.get_register_size() - frame.len()).as_str())
     };
+    encode_bits(frame, bit_sm, states_map)
+}
 
+
+fn encode_bits(mut frame: String, bit_sm: &mut BitSM, states_map: &HashMap<String, HashMap<String, String>>) -> String {
+    let mut result_frame = String::new();
     while !frame.is_empty() {
         let next_frame_bit = frame.remove(0);
         let current_state = bit_sm.get_current_state();
@@ -24,56 +38,91 @@ fn encode(mut frame: String, bit_sm: &mut BitSM, states_map: &HashMap<String, Ha
 }
 
 
-fn decode(frame: String, states_map: &HashMap<String, HashMap<String, String>>) -> String {
-    let mut all_state_steps = states_map.keys().map(
-        |key| { vec![StateStep::new(0, key.clone())] }
-    ).collect::<Vec<Vec<StateStep>>>();
-    let result_frame_len = frame.len() / 2;
-
-    for index in (0..frame.len()).step_by(2) {
-        let current_transition_bits = &frame[index..index+2];
-        for state_steps in &mut all_state_steps {
-            let state_steps_len = state_steps.len();
-            let next_key = {
-                let extreme_node = &mut state_steps[state_steps_len - 1];
-                let next_states = states_map.get(&extreme_node.state).unwrap();
-                let first_key = format!("{}{}", "1", &extreme_node.state[..extreme_node.state.len() - 1]);
-                let second_key= format!("{}{}", "0", &extreme_node.state[..extreme_node.state.len() - 1]);
-
-                let first_hd = binary_to_decimal(next_states.get(&first_key).unwrap()) ^ binary_to_decimal(current_transition_bits);
-                let second_hd = binary_to_decimal(next_states.get(&second_key).unwrap()) ^ binary_to_decimal(current_transition_bits);
-
-                let first_hd_ones = first_hd.count_ones();
-                let second_hd_ones = second_hd.count_ones();
-                if first_hd_ones < second_hd_ones {
-                    extreme_node.sum_hd += first_hd_ones as usize;
-                    first_key
-                } else {
-                    extreme_node.sum_hd += second_hd_ones as usize;
-                    second_key
-                }
-            };
-
-            let extreme_node_sum_hd = state_steps[state_steps_len - 1].sum_hd;
-            state_steps.push(StateStep::new(extreme_node_sum_hd, next_key));
-        }
-    }
-
-    let mut result_frame = String::new();
-    for state_steps in all_state_steps {
-        if state_steps[state_steps.len() - 1].sum_hd == 0 {
-            for step in &state_steps[1..result_frame_len + 1] {
-                result_frame.push(step.state.as_bytes()[0] as char)
+/// Encode a frame starting from the zero register with explicit termination.
+/// Tail-biting frames must be at least as long as the register.
+pub fn encode_terminated(
+    mut frame: String,
+    termination: Termination,
+    bit_sm: &mut BitSM,
+    states_map: &HashMap<String, HashMap<String, String>>
+) -> String {
+    let register_size = bit_sm.get_register_size();
+    match termination {
+        Termination::Truncated => {}
+        Termination::ZeroTail => frame.push_str(&"0".repeat(register_size)),
+        Termination::TailBiting => {
+            assert!(frame.len() >= register_size, "Tail-biting frame is shorter than the register");
+            // Регистр заполняется последними K-1 битами кадра
+            for bit in frame[frame.len() - register_size..].chars() {
+                bit_sm.shift_register(bit);
             }
         }
     }
-    result_frame
+    encode_bits(frame, bit_sm, states_map)
+}
+
+
+pub fn decode(frame: String, states_map: &HashMap<String, HashMap<String, String>>) -> String {
+    let trellis = Trellis::from_states_map(states_map);
+    bits_to_string(&decode_hard(&trellis, &bits_from_str(&frame)))
+}
+
+
+pub fn decode_soft(
+    symbols: &SoftSymbols,
+    metric: BranchMetric,
+    states_map: &HashMap<String, HashMap<String, String>>
+) -> String {
+    let trellis = Trellis::from_states_map(states_map);
+    bits_to_string(&viterbi_decode_soft(&trellis, symbols, metric))
+}
+
+
+pub fn decode_terminated(
+    frame: String,
+    termination: Termination,
+    states_map: &HashMap<String, HashMap<String, String>>
+) -> String {
+    let trellis = Trellis::from_states_map(states_map);
+    bits_to_string(&decode_hard_terminated(&trellis, &bits_from_str(&frame), termination))
+}
+
+
+pub fn decode_soft_terminated(
+    symbols: &SoftSymbols,
+    metric: BranchMetric,
+    termination: Termination,
+    states_map: &HashMap<String, HashMap<String, String>>
+) -> String {
+    let trellis = Trellis::from_states_map(states_map);
+    bits_to_string(&viterbi_decode_soft_terminated(&trellis, symbols, metric, termination))
+}
+
+
+pub fn encode_punctured(
+    frame: String,
+    rate: CodeRate,
+    bit_sm: &mut BitSM,
+    states_map: &HashMap<String, HashMap<String, String>>
+) -> String {
+    puncture(&encode(frame, bit_sm, states_map), rate)
+}
+
+
+pub fn decode_punctured(
+    frame: String,
+    rate: CodeRate,
+    states_map: &HashMap<String, HashMap<String, String>>
+) -> String {
+    let symbols = SoftSymbols::Llr(depuncture(&frame, rate));
+    decode_soft(&symbols, BranchMetric::Correlation, states_map)
 }
 
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_utils::{bpsk_llrs, flip_bits};
 
     #[test]
     fn frame_encoded_1() {
@@ -168,4 +217,183 @@ mod tests {
         );
     }
 
-}
\ No newline at end of file
+
+    #[test]
+    fn frame_decoded_with_single_error() {
+        let frame = flip_bits("111001011100010010110000", &[5]);
+        let bit_sm = BitSM::new();
+        let decoded_frame = decode(frame, &bit_sm.states_map());
+        assert_eq!(
+            decoded_frame,
+            "111010111010"
+        );
+    }
+
+    #[test]
+    fn frame_decoded_with_scattered_errors() {
+        let frame = String::from("1110110101000011110111001010101010101001001001001101111111101001101010010101010101001001001001010101001010100100110010111110101010010111010");
+        let mut bit_sm = BitSM::new();
+        let states_map = bit_sm.states_map();
+        let encoded_frame = encode(frame.clone(), &mut bit_sm, &states_map);
+        let corrupted_frame = flip_bits(&encoded_frame, &[3, 4, 40, 41, 90, 92, 150, 200, 201, 202]);
+        assert_eq!(decode(corrupted_frame, &states_map), frame);
+    }
+
+    #[test]
+    fn frame_decoded_with_errors_within_free_distance() {
+        let frame = String::from("0101001101111000000");
+        let mut bit_sm = BitSM::new();
+        let states_map = bit_sm.states_map();
+        let encoded_frame = encode(frame.clone(), &mut bit_sm, &states_map);
+        let corrupted_frame = flip_bits(&encoded_frame, &[2, 5, 7, 10]);
+        assert_eq!(decode(corrupted_frame, &states_map), frame);
+    }
+
+    fn weak_wrong_llrs(encoded_frame: &str, weak_indexes: &[usize]) -> Vec<f64> {
+        bpsk_llrs(encoded_frame, |index| if weak_indexes.contains(&index) { -0.1 } else { 1.0 })
+    }
+
+    #[test]
+    fn soft_decoding_fixes_weak_errors_beyond_hard_capability() {
+        let frame = String::from("0101001101111000000");
+        let mut bit_sm = BitSM::new();
+        let states_map = bit_sm.states_map();
+        let encoded_frame = encode(frame.clone(), &mut bit_sm, &states_map);
+        let weak_indexes = [1, 2, 3, 5, 6, 8];
+
+        let hard_frame = flip_bits(&encoded_frame, &weak_indexes);
+        assert_ne!(decode(hard_frame, &states_map), frame);
+
+        let symbols = SoftSymbols::Llr(weak_wrong_llrs(&encoded_frame, &weak_indexes));
+        assert_eq!(decode_soft(&symbols, BranchMetric::Correlation, &states_map), frame);
+        assert_eq!(decode_soft(&symbols, BranchMetric::Euclidean, &states_map), frame);
+    }
+
+    #[test]
+    fn quantized_frame_decoded() {
+        let frame = String::from("111010111010");
+        let mut bit_sm = BitSM::new();
+        let states_map = bit_sm.states_map();
+        let encoded_frame = encode(frame.clone(), &mut bit_sm, &states_map);
+        // 8-битные отсчёты: уверенные значения, одно ослабленное и два ошибочных
+        let mut values = encoded_frame.chars()
+            .map(|bit| if bit == '0' { 20 } else { 235 })
+            .collect::<Vec<u8>>();
+        values[0] = 120;
+        values[3] = 140;
+        values[9] = 200;
+        let symbols = SoftSymbols::Quantized { bits: 8, values };
+        assert_eq!(decode_soft(&symbols, BranchMetric::Euclidean, &states_map), frame);
+
+        let values_3_bit = encoded_frame.chars()
+            .map(|bit| if bit == '0' { 1 } else { 6 })
+            .collect::<Vec<u8>>();
+        let symbols = SoftSymbols::Quantized { bits: 3, values: values_3_bit };
+        assert_eq!(decode_soft(&symbols, BranchMetric::Correlation, &states_map), frame);
+    }
+
+    #[test]
+    fn punctured_frames_roundtrip() {
+        let frame = String::from("1110110101000011110111001010101010101001001001001101111111101001101010010101010101001001");
+        for rate in [CodeRate::Rate1_2, CodeRate::Rate2_3, CodeRate::Rate3_4, CodeRate::Rate5_6, CodeRate::Rate7_8] {
+            let mut bit_sm = BitSM::new();
+            let states_map = bit_sm.states_map();
+            let encoded_frame = encode_punctured(frame.clone(), rate, &mut bit_sm, &states_map);
+            assert_eq!(decode_punctured(encoded_frame, rate, &states_map), frame);
+        }
+    }
+
+    #[test]
+    fn punctured_frame_decoded_with_errors() {
+        let frame = String::from("1110110101000011110111001010101010101001001001001101111111101001101010010101010101001001");
+        let mut bit_sm = BitSM::new();
+        let states_map = bit_sm.states_map();
+        let encoded_frame = encode_punctured(frame.clone(), CodeRate::Rate2_3, &mut bit_sm, &states_map);
+        let corrupted_frame = flip_bits(&encoded_frame, &[10, 60]);
+        assert_eq!(decode_punctured(corrupted_frame, CodeRate::Rate2_3, &states_map), frame);
+    }
+
+    #[test]
+    fn teaching_code_k3_encoded() {
+        let mut bit_sm = BitSM::with_generators(3, &[0o7, 0o5]);
+        let states_map = bit_sm.states_map();
+        let encoded_frame = encode(String::from("1011"), &mut bit_sm, &states_map);
+        assert_eq!(encoded_frame, "11100001");
+        assert_eq!(decode(flip_bits(&encoded_frame, &[2]), &states_map), "1011");
+    }
+
+    #[test]
+    fn gsm_and_lte_codes_roundtrip() {
+        use crate::frame_fix_algos::state_machine::{GSM_GENERATORS, LTE_GENERATORS};
+        let frame = String::from("0101001101111000010110");
+        for (constraint_length, generators) in [(5, GSM_GENERATORS.to_vec()), (7, LTE_GENERATORS.to_vec())] {
+            let mut bit_sm = BitSM::with_generators(constraint_length, &generators);
+            let states_map = bit_sm.states_map();
+            let encoded_frame = encode(frame.clone(), &mut bit_sm, &states_map);
+            assert_eq!(encoded_frame.len(), frame.len() * generators.len());
+            assert_eq!(decode(flip_bits(&encoded_frame, &[4, 30]), &states_map), frame);
+        }
+    }
+
+    #[test]
+    fn zero_tail_frame_flushed() {
+        let mut bit_sm = BitSM::new();
+        let states_map = bit_sm.states_map();
+        let encoded_frame = encode_terminated(String::from("111"), Termination::ZeroTail, &mut bit_sm, &states_map);
+        assert_eq!(encoded_frame.len(), 18);
+        assert!(encoded_frame.starts_with("111001010001"));
+        assert_eq!(bit_sm.get_current_state(), "000000");
+        assert_eq!(decode_terminated(encoded_frame, Termination::ZeroTail, &states_map), "111");
+    }
+
+    #[test]
+    fn zero_tail_protects_last_bits() {
+        let frame = String::from("0101001101111011");
+        let mut bit_sm = BitSM::new();
+        let states_map = bit_sm.states_map();
+        let encoded_frame = encode_terminated(frame.clone(), Termination::ZeroTail, &mut bit_sm, &states_map);
+        let last = encoded_frame.len() - 1;
+        let corrupted_frame = flip_bits(&encoded_frame, &[last - 14, last - 10, last - 4, last]);
+        assert_eq!(decode_terminated(corrupted_frame, Termination::ZeroTail, &states_map), frame);
+    }
+
+    #[test]
+    fn tail_biting_frame_rotates_with_input() {
+        let frame = String::from("0101001101111011");
+        let rotated_frame = format!("{}{}", &frame[1..], &frame[..1]);
+        let mut bit_sm = BitSM::new();
+        let states_map = bit_sm.states_map();
+        let encoded_frame = encode_terminated(frame.clone(), Termination::TailBiting, &mut bit_sm, &states_map);
+        let mut bit_sm = BitSM::new();
+        let encoded_rotated = encode_terminated(rotated_frame, Termination::TailBiting, &mut bit_sm, &states_map);
+
+        assert_eq!(encoded_frame.len(), frame.len() * 2);
+        assert_eq!(encoded_rotated, format!("{}{}", &encoded_frame[2..], &encoded_frame[..2]));
+    }
+
+    #[test]
+    fn tail_biting_frame_decoded_with_errors() {
+        let frame = String::from("1110110101000011110111001010101010101001001001001101");
+        let mut bit_sm = BitSM::new();
+        let states_map = bit_sm.states_map();
+        let encoded_frame = encode_terminated(frame.clone(), Termination::TailBiting, &mut bit_sm, &states_map);
+        let last = encoded_frame.len() - 1;
+        let corrupted_frame = flip_bits(&encoded_frame, &[0, 3, 50, last - 1]);
+        assert_eq!(decode_terminated(corrupted_frame, Termination::TailBiting, &states_map), frame);
+
+        let symbols = SoftSymbols::Llr(weak_wrong_llrs(&encoded_frame, &[1, 2, 60, last]));
+        assert_eq!(
+            decode_soft_terminated(&symbols, BranchMetric::Correlation, Termination::TailBiting, &states_map),
+            frame
+        );
+    }
+
+    #[test]
+    fn truncated_frame_not_padded() {
+        let mut bit_sm = BitSM::new();
+        let states_map = bit_sm.states_map();
+        let encoded_frame = encode_terminated(String::from("111"), Termination::Truncated, &mut bit_sm, &states_map);
+        assert_eq!(encoded_frame, "111001");
+        assert_eq!(decode_terminated(encoded_frame, Termination::Truncated, &states_map), "111");
+    }
+}
@@ -0,0 +1,360 @@
+// Полярные коды длины N = 2^n: вектор u из N бит умножается на F^(кронекерова
+// степень n), F = [[1, 0], [1, 1]], без перестановки с обращением бит.
+// Рекурсивно x = ((u1 ^ u2) * G', u2 * G'), где u1, u2 - половины u. Часть
+// входов u "заморожена" (всегда 0), данные идут в самые надёжные позиции.
+// Надёжность задаётся либо параметром Бхаттачарьи: на верхнем уровне каждая
+// пара каналов с параметром z даёт худший канал 2z - z^2 (первая половина u)
+// и лучший z^2 (вторая половина), далее рекурсивно; либо готовой
+// последовательностью индексов от наименее к наиболее надёжному (как в 5G NR).
+//
+// Декодирование последовательным исключением (SC) повторяет рекурсию:
+// LLR для первой половины - f(a, b) = sign(a) sign(b) min(|a|, |b|)
+// (приближение min-sum), по решениям для неё считается LLR второй половины
+// g(a, b, x) = b + (1 - 2x) a. Списочное декодирование (SCL) хранит до L путей,
+// на каждом информационном бите путь раздваивается, остаются L путей с
+// наименьшей метрикой: решение против знака LLR добавляет к метрике |LLR|.
+// С CRC среди итоговых путей выбирается лучший, у которого сходится CRC.
+// Положительный LLR - ноль.
+
+use crate::frame_fix_algos::block_code::BlockCode;
+use crate::frame_fix_algos::decode_report::{BlockDecodeResult, DecodeStatus};
+use crate::frame_fix_algos::viterbi::SoftSymbols;
+use crate::utils::{bits_from_str, bits_to_string};
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PolarConstruction {
+    /// Bhattacharyya parameters of a BPSK AWGN channel at the design Eb/N0
+    Bhattacharyya { design_snr_db: f64 },
+    /// Indexes of u from the least to the most reliable
+    ReliabilitySequence(Vec<usize>),
+}
+
+
+pub struct PolarCode {
+    data_bits: usize,
+    frozen: Vec<bool>,
+    information_positions: Vec<usize>,
+    /// CRC generator with the highest term, empty without CRC
+    crc_generator: Vec<u8>,
+}
+
+/// Decoding paths of the list decoder
+struct Paths {
+    metrics: Vec<f64>,
+    decisions: Vec<Vec<u8>>,
+}
+
+impl PolarCode {
+    pub fn new(codeword_bits: usize, data_bits: usize, construction: PolarConstruction) -> Self {
+        Self::with_crc(codeword_bits, data_bits, construction, "")
+    }
+
+    /// Polar code with a CRC like "1100001" (x^6 + x^5 + 1) appended to data before encoding
+    pub fn with_crc(codeword_bits: usize, data_bits: usize, construction: PolarConstruction, crc_generator: &str) -> Self {
+        assert!(codeword_bits.is_power_of_two() && codeword_bits >= 2, "Polar code length must be a power of two");
+        let crc_generator = bits_from_str(crc_generator.trim_start_matches('0'));
+        let crc_bits = crc_generator.len().saturating_sub(1);
+        let information_bits = data_bits + crc_bits;
+        assert!(data_bits > 0 && information_bits <= codeword_bits, "Data and CRC don't fit in {codeword_bits} bits");
+
+        let mut information_positions = match construction {
+            PolarConstruction::Bhattacharyya { design_snr_db } => {
+                // Скорость по всем незамороженным битам: CRC тоже занимает информационные позиции
+                let rate = information_bits as f64 / codeword_bits as f64;
+                bhattacharyya_order(codeword_bits, rate * 10f64.powf(design_snr_db / 10.0))
+            }
+            PolarConstruction::ReliabilitySequence(sequence) => {
+                let mut seen = vec![false; codeword_bits];
+                let is_permutation = sequence.len() == codeword_bits
+                    && sequence.iter().all(|&position| position < codeword_bits && !std::mem::replace(&mut seen[position], true));
+                assert!(is_permutation, "Reliability sequence must be a permutation of 0..{codeword_bits}");
+                sequence
+            }
+        };
+        // Информационные позиции - самые надёжные, в конце последовательности
+        information_positions.drain(..codeword_bits - information_bits);
+        information_positions.sort_unstable();
+        let mut frozen = vec![true; codeword_bits];
+        for &position in &information_positions {
+            frozen[position] = false;
+        }
+        PolarCode { data_bits, frozen, information_positions, crc_generator }
+    }
+
+    /// Indexes of u carrying data followed by CRC
+    pub fn information_positions(&self) -> &[usize] { &self.information_positions }
+
+    pub fn crc_bits(&self) -> usize { self.crc_generator.len().saturating_sub(1) }
+
+    fn crc_remainder(&self, bits: &[u8]) -> Vec<u8> {
+        let crc_bits = self.crc_bits();
+        let mut remainder = bits.to_vec();
+        remainder.resize(bits.len() + crc_bits, 0);
+        for index in 0..bits.len() {
+            if remainder[index] == 1 {
+                for (offset, &generator_bit) in self.crc_generator.iter().enumerate() {
+                    remainder[index + offset] ^= generator_bit;
+                }
+            }
+        }
+        remainder.split_off(bits.len())
+    }
+
+    fn encode_bits(&self, u: &[u8]) -> Vec<u8> {
+        let mut codeword = u.to_vec();
+        let mut half = codeword.len() / 2;
+        while half > 0 {
+            for block in codeword.chunks_exact_mut(2 * half) {
+                let (first, second) = block.split_at_mut(half);
+                for (bit, &other) in first.iter_mut().zip(second.iter()) {
+                    *bit ^= other;
+                }
+            }
+            half /= 2;
+        }
+        codeword
+    }
+
+    /// Successive cancellation decoding
+    pub fn decode_sc(&self, symbols: &SoftSymbols) -> BlockDecodeResult {
+        self.decode_list(symbols, 1)
+    }
+
+    /// Successive cancellation list decoding keeping up to `list_size` paths,
+    /// with a CRC the best path passing it is chosen
+    pub fn decode_list(&self, symbols: &SoftSymbols, list_size: usize) -> BlockDecodeResult {
+        assert!(list_size >= 1, "List must hold at least one path");
+        let llrs = symbols.to_llrs();
+        assert_eq!(llrs.len(), self.frozen.len(), "Polar codeword must have {} symbols", self.frozen.len());
+
+        let mut paths = Paths { metrics: vec![0.0], decisions: vec![Vec::with_capacity(llrs.len())] };
+        self.decode_node(vec![llrs.clone()], 0, list_size, &mut paths);
+
+        let mut order = (0..paths.metrics.len()).collect::<Vec<usize>>();
+        order.sort_by(|&first, &second| paths.metrics[first].total_cmp(&paths.metrics[second]));
+        let information = |path: usize| -> Vec<u8> {
+            self.information_positions.iter().map(|&position| paths.decisions[path][position]).collect()
+        };
+        let passing = order.iter()
+            .copied()
+            .find(|&path| self.crc_remainder(&information(path)).iter().all(|&bit| bit == 0));
+
+        let received = llrs.iter().map(|&llr| (llr < 0.0) as u8).collect::<Vec<u8>>();
+        let Some(path) = passing else {
+            return BlockDecodeResult {
+                data: bits_to_string(&information(order[0])[..self.data_bits]),
+                corrected_positions: Vec::new(),
+                status: DecodeStatus::Uncorrectable,
+            };
+        };
+        let corrected_positions = self.encode_bits(&paths.decisions[path]).iter()
+            .zip(&received)
+            .enumerate()
+            .filter(|(_, (decoded, received))| decoded != received)
+            .map(|(position, _)| position)
+            .collect::<Vec<usize>>();
+        BlockDecodeResult {
+            data: bits_to_string(&information(path)[..self.data_bits]),
+            status: if corrected_positions.is_empty() { DecodeStatus::Clean } else { DecodeStatus::Corrected },
+            corrected_positions,
+        }
+    }
+
+    /// Decodes u[offset..offset + len] for every path from LLRs of its codeword part.
+    /// Returns re-encoded bits of every surviving path and the input path it came from.
+    fn decode_node(&self, llrs: Vec<Vec<f64>>, offset: usize, list_size: usize, paths: &mut Paths) -> (Vec<Vec<u8>>, Vec<usize>) {
+        let len = llrs[0].len();
+        if len == 1 {
+            return self.decode_leaf(&llrs, offset, list_size, paths);
+        }
+        let half = len / 2;
+        let first_llrs = llrs.iter()
+            .map(|path_llrs| (0..half).map(|index| min_sum(path_llrs[index], path_llrs[index + half])).collect())
+            .collect();
+        let (first_bits, first_origins) = self.decode_node(first_llrs, offset, list_size, paths);
+
+        let second_llrs = first_bits.iter().zip(&first_origins).map(|(bits, &origin)| {
+            let path_llrs = &llrs[origin];
+            (0..half).map(|index| {
+                let sign = if bits[index] == 1 { -1.0 } else { 1.0 };
+                path_llrs[index + half] + sign * path_llrs[index]
+            }).collect()
+        }).collect();
+        let (second_bits, second_origins) = self.decode_node(second_llrs, offset + half, list_size, paths);
+
+        let bits = second_bits.iter().zip(&second_origins).map(|(second, &origin)| {
+            let first = &first_bits[origin];
+            first.iter().zip(second).map(|(a, b)| a ^ b).chain(second.iter().copied()).collect()
+        }).collect();
+        let origins = second_origins.iter().map(|&origin| first_origins[origin]).collect();
+        (bits, origins)
+    }
+
+    fn decode_leaf(&self, llrs: &[Vec<f64>], position: usize, list_size: usize, paths: &mut Paths) -> (Vec<Vec<u8>>, Vec<usize>) {
+        if self.frozen[position] {
+            for (path, path_llrs) in llrs.iter().enumerate() {
+                if path_llrs[0] < 0.0 {
+                    paths.metrics[path] -= path_llrs[0];
+                }
+                paths.decisions[path].push(0);
+            }
+            return (vec![vec![0]; llrs.len()], (0..llrs.len()).collect());
+        }
+
+        // Каждый путь раздваивается, остаются list_size лучших продолжений
+        let mut candidates = llrs.iter().enumerate().flat_map(|(path, path_llrs)| {
+            let llr = path_llrs[0];
+            let metric = paths.metrics[path];
+            [(path, 0u8, metric + (-llr).max(0.0)), (path, 1u8, metric + llr.max(0.0))]
+        }).collect::<Vec<(usize, u8, f64)>>();
+        candidates.sort_by(|first, second| first.2.total_cmp(&second.2));
+        candidates.truncate(list_size);
+
+        // Решения путей копируются целиком на каждом информационном бите: O(L·N²) памяти и времени.
+        // Для кодов в несколько тысяч бит нужны общие префиксы (ленивое копирование), здесь N мало
+        let decisions = candidates.iter().map(|&(path, bit, _)| {
+            let mut decisions = paths.decisions[path].clone();
+            decisions.push(bit);
+            decisions
+        }).collect();
+        *paths = Paths { metrics: candidates.iter().map(|candidate| candidate.2).collect(), decisions };
+        (
+            candidates.iter().map(|&(_, bit, _)| vec![bit]).collect(),
+            candidates.iter().map(|&(path, _, _)| path).collect(),
+        )
+    }
+}
+
+impl BlockCode for PolarCode {
+    fn codeword_bits(&self) -> usize { self.frozen.len() }
+
+    fn data_bits(&self) -> usize { self.data_bits }
+
+    fn encode(&self, data: &str) -> String {
+        assert_eq!(data.len(), self.data_bits, "Polar code encodes {} bits", self.data_bits);
+        let mut information = bits_from_str(data);
+        information.extend(self.crc_remainder(&information));
+        let mut u = vec![0u8; self.frozen.len()];
+        for (&position, &bit) in self.information_positions.iter().zip(&information) {
+            u[position] = bit;
+        }
+        bits_to_string(&self.encode_bits(&u))
+    }
+
+    /// SC decoding of hard decisions
+    fn decode(&self, codeword: &str) -> BlockDecodeResult {
+        let llrs = codeword.bytes().map(|bit| if bit == b'0' { 1.0 } else { -1.0 }).collect();
+        self.decode_sc(&SoftSymbols::Llr(llrs))
+    }
+}
+
+
+fn min_sum(first: f64, second: f64) -> f64 {
+    first.signum() * second.signum() * first.abs().min(second.abs())
+}
+
+/// Indexes of u sorted from the largest to the smallest Bhattacharyya parameter
+fn bhattacharyya_order(codeword_bits: usize, design_snr: f64) -> Vec<usize> {
+    let stages = codeword_bits.trailing_zeros();
+    // ln z, чтобы параметры хороших каналов не обращались в ноль
+    let log_parameters = (0..codeword_bits).map(|index| {
+        (0..stages).rev().fold(-design_snr, |log_z: f64, stage| {
+            if (index >> stage) & 1 == 1 { 2.0 * log_z } else { log_z + (2.0 - log_z.exp()).ln() }
+        })
+    }).collect::<Vec<f64>>();
+    let mut order = (0..codeword_bits).collect::<Vec<usize>>();
+    order.sort_by(|&first, &second| log_parameters[second].total_cmp(&log_parameters[first]).then(first.cmp(&second)));
+    order
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame_fix_algos::prbs::{PrbsGenerator, PrbsPattern};
+    use crate::test_utils::bpsk_llrs;
+
+    // CRC-6 из 5G NR: x^6 + x^5 + 1
+    const CRC6: &str = "1100001";
+
+    /// BPSK LLRs with deterministic pseudo-Gaussian noise: sum of uniform values
+    fn noisy_llrs(codeword: &str, noise_level: f64, seed: &mut u64) -> Vec<f64> {
+        // Шум симметричен, поэтому его можно отсчитывать от модуля сигнала
+        bpsk_llrs(codeword, |_| {
+            let noise = (0..12).map(|_| {
+                *seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                (*seed >> 11) as f64 / (1u64 << 53) as f64
+            }).sum::<f64>() - 6.0;
+            2.0 * (1.0 + noise_level * noise) / (noise_level * noise_level)
+        })
+    }
+
+    #[test]
+    fn bhattacharyya_selects_reliable_positions() {
+        let code = PolarCode::new(8, 4, PolarConstruction::Bhattacharyya { design_snr_db: 0.0 });
+        assert_eq!(code.information_positions(), &[3, 5, 6, 7]);
+        // Строка i матрицы F^3 - единицы в позициях j, биты которых входят в биты i
+        assert_eq!(code.encode("0001"), "11111111");
+        assert_eq!(code.encode("1000"), "11110000");
+        assert_eq!(code.encode("0100"), "11001100");
+    }
+
+    #[test]
+    fn reliability_sequence_used_as_given() {
+        let sequence = vec![0, 1, 2, 4, 8, 3, 5, 6, 9, 10, 12, 7, 11, 13, 14, 15];
+        let code = PolarCode::new(16, 5, PolarConstruction::ReliabilitySequence(sequence));
+        assert_eq!(code.information_positions(), &[7, 11, 13, 14, 15]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Reliability sequence must be a permutation of 0..4")]
+    fn reliability_sequence_must_be_permutation() {
+        PolarCode::new(4, 2, PolarConstruction::ReliabilitySequence(vec![0, 1, 1, 3]));
+    }
+
+    #[test]
+    fn sc_decodes_noiseless_and_hard_errors() {
+        let code = PolarCode::new(32, 16, PolarConstruction::Bhattacharyya { design_snr_db: 2.0 });
+        let data = PrbsGenerator::new(PrbsPattern::Prbs7).generate(16);
+        let codeword = code.encode(&data);
+        let result = code.decode(&codeword);
+        assert_eq!(result.data, data);
+        assert_eq!(result.status, DecodeStatus::Clean);
+
+        let mut seed = 7;
+        let llrs = noisy_llrs(&codeword, 0.5, &mut seed);
+        let result = code.decode_sc(&SoftSymbols::Llr(llrs));
+        assert_eq!(result.data, data);
+    }
+
+    #[test]
+    fn crc_aided_list_decoding_outperforms_sc() {
+        let code = PolarCode::with_crc(128, 58, PolarConstruction::Bhattacharyya { design_snr_db: 1.5 }, CRC6);
+        let mut generator = PrbsGenerator::new(PrbsPattern::Prbs15);
+        let mut seed = 1;
+        let (mut sc_failures, mut list_failures) = (0, 0);
+        for _ in 0..100 {
+            let data = generator.generate(58);
+            let llrs = SoftSymbols::Llr(noisy_llrs(&code.encode(&data), 0.8, &mut seed));
+            sc_failures += (code.decode_sc(&llrs).data != data) as usize;
+            list_failures += (code.decode_list(&llrs, 8).data != data) as usize;
+        }
+        assert!(sc_failures > 0);
+        assert!(list_failures * 2 < sc_failures, "SC {sc_failures}, SCL {list_failures}");
+    }
+
+    #[test]
+    fn crc_failure_reported() {
+        let code = PolarCode::with_crc(16, 4, PolarConstruction::Bhattacharyya { design_snr_db: 0.0 }, CRC6);
+        assert_eq!(code.crc_bits(), 6);
+        let codeword = code.encode("1011");
+        let llrs = bpsk_llrs(&codeword, |_| 3.0);
+        assert_eq!(code.decode_list(&SoftSymbols::Llr(llrs), 4).data, "1011");
+
+        // Все символы стёрты в сторону единицы: ни один путь не проходит CRC
+        let result = code.decode_list(&SoftSymbols::Quantized { bits: 3, values: vec![7; 16] }, 1);
+        assert_eq!(result.status, DecodeStatus::Uncorrectable);
+        assert!(result.corrected_positions.is_empty());
+    }
+}
@@ -0,0 +1,265 @@
+// Псевдослучайные двоичные последовательности (PRBS) по ITU-T O.150.
+// Генератор - регистр сдвига с линейной обратной связью по полиному x^a + x^b + 1,
+// приёмник (checker) самосинхронизируется: сначала заполняет регистр принятыми
+// битами, а после захвата предсказывает каждый следующий бит сам и считает ошибки.
+// Нулевой регистр предсказывает нули бесконечно и в PRBS не встречается, поэтому
+// на нём синхронизация не засчитывается - иначе мёртвая линия выглядела бы чистой.
+
+use std::collections::VecDeque;
+
+use crate::frame_fix_algos::state_machine::BitSM;
+use crate::utils::convert_char_bit_to_int;
+
+// Количество подряд верно предсказанных бит, после которого checker считает себя синхронизированным
+const LOCK_BITS: usize = 64;
+// Окно и порог ошибок, при превышении которого синхронизация считается потерянной
+const LOSS_WINDOW: usize = 64;
+const LOSS_THRESHOLD: usize = 16;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrbsPattern {
+    Prbs7,
+    Prbs9,
+    Prbs15,
+    Prbs23,
+    Prbs31,
+}
+
+impl PrbsPattern {
+    /// Taps of the feedback polynomial x^a + x^b + 1 as (a, b)
+    pub fn taps(&self) -> (usize, usize) {
+        match self {
+            PrbsPattern::Prbs7 => (7, 6),
+            PrbsPattern::Prbs9 => (9, 5),
+            PrbsPattern::Prbs15 => (15, 14),
+            PrbsPattern::Prbs23 => (23, 18),
+            PrbsPattern::Prbs31 => (31, 28),
+        }
+    }
+
+    pub fn register_size(&self) -> usize { self.taps().0 }
+
+    /// Length of the sequence before it repeats, 2^n - 1
+    pub fn period(&self) -> usize { (1usize << self.register_size()) - 1 }
+
+    fn feedback_bit(&self, state: &str) -> char {
+        let (first_tap, second_tap) = self.taps();
+        let feedback = convert_char_bit_to_int(state.as_bytes()[first_tap - 1] as char)
+            ^ convert_char_bit_to_int(state.as_bytes()[second_tap - 1] as char);
+        if feedback == 1 { '1' } else { '0' }
+    }
+}
+
+
+pub struct PrbsGenerator {
+    pattern: PrbsPattern,
+    bit_sm: BitSM,
+}
+
+impl PrbsGenerator {
+    /// Generator seeded with the all-ones register
+    pub fn new(pattern: PrbsPattern) -> Self {
+        let seed = "1".repeat(pattern.register_size());
+        PrbsGenerator { pattern, bit_sm: BitSM::from_state(&seed) }
+    }
+
+    pub fn next_bit(&mut self) -> char {
+        let next_bit = self.pattern.feedback_bit(self.bit_sm.get_current_state());
+        self.bit_sm.shift_register(next_bit);
+        next_bit
+    }
+
+    pub fn generate(&mut self, frame_len: usize) -> String {
+        (0..frame_len).map(|_| self.next_bit()).collect()
+    }
+}
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrbsReport {
+    pub bits_checked: usize,
+    pub bit_errors: usize,
+    pub lock_losses: usize,
+    pub locked: bool,
+}
+
+impl PrbsReport {
+    /// Bit error rate over all bits checked while locked
+    pub fn ber(&self) -> f64 {
+        if self.bits_checked == 0 {
+            return 0.0;
+        }
+        self.bit_errors as f64 / self.bits_checked as f64
+    }
+}
+
+
+pub struct PrbsChecker {
+    pattern: PrbsPattern,
+    bit_sm: BitSM,
+    loaded_bits: usize,
+    sync_bits: usize,
+    locked: bool,
+    error_window: VecDeque<bool>,
+    window_errors: usize,
+    bits_checked: usize,
+    bit_errors: usize,
+    lock_losses: usize,
+}
+
+impl PrbsChecker {
+    pub fn new(pattern: PrbsPattern) -> Self {
+        PrbsChecker {
+            pattern,
+            bit_sm: BitSM::from_state(&"0".repeat(pattern.register_size())),
+            loaded_bits: 0,
+            sync_bits: 0,
+            locked: false,
+            error_window: VecDeque::with_capacity(LOSS_WINDOW),
+            window_errors: 0,
+            bits_checked: 0,
+            bit_errors: 0,
+            lock_losses: 0,
+        }
+    }
+
+    pub fn check_bit(&mut self, bit: char) {
+        let predicted_bit = self.pattern.feedback_bit(self.bit_sm.get_current_state());
+        if !self.locked {
+            // Пока нет синхронизации, в регистр загружаются принятые биты
+            let register_loaded = self.loaded_bits >= self.pattern.register_size()
+                && self.bit_sm.get_current_state().contains('1');
+            if register_loaded && predicted_bit == bit {
+                self.sync_bits += 1;
+            } else {
+                self.sync_bits = 0;
+            }
+            self.bit_sm.shift_register(bit);
+            self.loaded_bits += 1;
+            if self.sync_bits >= LOCK_BITS {
+                self.locked = true;
+                self.error_window.clear();
+                self.window_errors = 0;
+            }
+            return;
+        }
+
+        // После захвата регистр работает автономно, чтобы ошибка канала не размножалась
+        self.bit_sm.shift_register(predicted_bit);
+        let is_error = predicted_bit != bit;
+        self.bits_checked += 1;
+        if is_error {
+            self.bit_errors += 1;
+            self.window_errors += 1;
+        }
+        self.error_window.push_back(is_error);
+        if self.error_window.len() > LOSS_WINDOW && self.error_window.pop_front() == Some(true) {
+            self.window_errors -= 1;
+        }
+
+        if self.window_errors > LOSS_THRESHOLD {
+            self.locked = false;
+            self.lock_losses += 1;
+            self.loaded_bits = 0;
+            self.sync_bits = 0;
+        }
+    }
+
+    pub fn check_frame(&mut self, frame: &str) {
+        for bit in frame.chars() {
+            self.check_bit(bit);
+        }
+    }
+
+    pub fn report(&self) -> PrbsReport {
+        PrbsReport {
+            bits_checked: self.bits_checked,
+            bit_errors: self.bit_errors,
+            lock_losses: self.lock_losses,
+            locked: self.locked,
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::flip_bits;
+
+    #[test]
+    fn prbs7_sequence_repeats_with_full_period() {
+        let mut generator = PrbsGenerator::new(PrbsPattern::Prbs7);
+        let sequence = generator.generate(2 * PrbsPattern::Prbs7.period());
+        let (first_period, second_period) = sequence.split_at(127);
+        assert_eq!(first_period, second_period);
+        assert_eq!(first_period.matches('1').count(), 64);
+        assert_eq!(&first_period[..16], "0000001000001100");
+    }
+
+    #[test]
+    fn prbs9_has_maximal_period() {
+        let mut generator = PrbsGenerator::new(PrbsPattern::Prbs9);
+        let sequence = generator.generate(PrbsPattern::Prbs9.period() + 9);
+        assert_eq!(&sequence[..9], &sequence[511..]);
+        assert_eq!(sequence[..511].matches('1').count(), 256);
+        assert!(!sequence[1..511].contains(&sequence[..9]));
+    }
+
+    #[test]
+    fn checker_locks_on_clean_pattern() {
+        let mut generator = PrbsGenerator::new(PrbsPattern::Prbs15);
+        // Checker starts in the middle of the sequence
+        generator.generate(1000);
+        let mut checker = PrbsChecker::new(PrbsPattern::Prbs15);
+        checker.check_frame(&generator.generate(2000));
+
+        let report = checker.report();
+        assert!(report.locked);
+        assert_eq!(report.bit_errors, 0);
+        assert_eq!(report.bits_checked, 2000 - 15 - LOCK_BITS);
+        assert_eq!(report.ber(), 0.0);
+    }
+
+    #[test]
+    fn checker_counts_bit_errors() {
+        let mut generator = PrbsGenerator::new(PrbsPattern::Prbs23);
+        let frame = flip_bits(&generator.generate(1000), &[200, 350, 351, 900]);
+        let mut checker = PrbsChecker::new(PrbsPattern::Prbs23);
+        checker.check_frame(&frame);
+
+        let report = checker.report();
+        assert!(report.locked);
+        assert_eq!(report.bit_errors, 4);
+        assert_eq!(report.lock_losses, 0);
+        assert_eq!(report.ber(), 4.0 / (1000 - 23 - LOCK_BITS) as f64);
+    }
+
+    #[test]
+    fn checker_never_locks_on_dead_link() {
+        let mut checker = PrbsChecker::new(PrbsPattern::Prbs7);
+        checker.check_frame(&"0".repeat(1000));
+
+        let report = checker.report();
+        assert!(!report.locked);
+        assert_eq!(report.bits_checked, 0);
+    }
+
+    #[test]
+    fn checker_reports_lock_loss_and_relocks() {
+        let mut generator = PrbsGenerator::new(PrbsPattern::Prbs31);
+        let mut frame = generator.generate(300);
+        // Разрыв последовательности - приёмник должен потерять синхронизацию
+        let mut other_generator = PrbsGenerator::new(PrbsPattern::Prbs31);
+        other_generator.generate(12345);
+        frame.push_str(&other_generator.generate(500));
+
+        let mut checker = PrbsChecker::new(PrbsPattern::Prbs31);
+        checker.check_frame(&frame);
+
+        let report = checker.report();
+        assert_eq!(report.lock_losses, 1);
+        assert!(report.locked);
+    }
+}
@@ -0,0 +1,346 @@
+// Произведение двух блочных кодов. Данные записываются по строкам в матрицу
+// k_c x k_r, каждая строка кодируется кодом строк (n_r, k_r), затем каждый из
+// n_r столбцов - кодом столбцов (n_c, k_c). Кодовое слово - матрица n_c x n_r,
+// прочитанная по строкам, так что пакет ошибок вдоль строки попадает в разные
+// столбцы по одной ошибке.
+// Итеративное жёсткое декодирование: строки и столбцы по очереди декодируются
+// своими кодами и исправления вносятся в матрицу, пока очередной проход не
+// перестанет что-либо исправлять. Строки и столбцы, которые в конце остаются
+// с ошибкой, возвращаются как остаточные ошибки, а данные читаются из итоговой
+// матрицы как есть, без повторного декодирования. Если осталась ровно одна
+// строка и один столбец, бит на их пересечении инвертируется - так исправляется
+// одиночная ошибка двумерной чётности (LRC/VRC), где код строк и столбцов
+// только обнаруживает ошибки.
+
+use crate::frame_fix_algos::block_code::BlockCode;
+use crate::frame_fix_algos::decode_report::{BlockDecodeResult, DecodeStatus};
+use crate::utils::{bits_from_str, bits_to_string};
+
+const DEFAULT_ITERATIONS: usize = 4;
+
+
+/// Single parity check (k + 1, k) code: an even parity bit after the data, errors are only detected
+pub struct SingleParityCode {
+    data_bits: usize,
+}
+
+impl SingleParityCode {
+    pub fn new(data_bits: usize) -> Self {
+        assert!(data_bits > 0, "Parity code needs at least one data bit");
+        SingleParityCode { data_bits }
+    }
+}
+
+impl BlockCode for SingleParityCode {
+    fn codeword_bits(&self) -> usize { self.data_bits + 1 }
+
+    fn data_bits(&self) -> usize { self.data_bits }
+
+    fn encode(&self, data: &str) -> String {
+        assert_eq!(data.len(), self.data_bits, "Parity code encodes {} bits", self.data_bits);
+        let parity = if data.matches('1').count() % 2 == 1 { '1' } else { '0' };
+        format!("{}{}", data, parity)
+    }
+
+    fn decode(&self, codeword: &str) -> BlockDecodeResult {
+        assert_eq!(codeword.len(), self.data_bits + 1, "Parity codeword must have {} bits", self.data_bits + 1);
+        BlockDecodeResult {
+            data: codeword[..self.data_bits].to_string(),
+            corrected_positions: Vec::new(),
+            status: if codeword.matches('1').count().is_multiple_of(2) { DecodeStatus::Clean } else { DecodeStatus::Uncorrectable },
+        }
+    }
+
+    fn extract_data(&self, codeword: &str) -> String {
+        assert_eq!(codeword.len(), self.data_bits + 1, "Parity codeword must have {} bits", self.data_bits + 1);
+        codeword[..self.data_bits].to_string()
+    }
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProductDecodeResult {
+    pub data: String,
+    /// Zero-based indexes of corrected bits in the row-major codeword
+    pub corrected_positions: Vec<usize>,
+    /// Row and column passes made
+    pub iterations: usize,
+    /// Rows and columns still failing to decode after the last pass
+    pub failed_rows: Vec<usize>,
+    pub failed_columns: Vec<usize>,
+    pub status: DecodeStatus,
+}
+
+
+pub struct ProductCode<R: BlockCode, C: BlockCode> {
+    row_code: R,
+    column_code: C,
+}
+
+impl<R: BlockCode, C: BlockCode> ProductCode<R, C> {
+    pub fn new(row_code: R, column_code: C) -> Self {
+        ProductCode { row_code, column_code }
+    }
+
+    pub fn row_code(&self) -> &R { &self.row_code }
+
+    pub fn column_code(&self) -> &C { &self.column_code }
+
+    fn columns_count(&self) -> usize { self.row_code.codeword_bits() }
+
+    fn rows_count(&self) -> usize { self.column_code.codeword_bits() }
+
+    fn row(&self, bits: &[u8], row: usize) -> String {
+        bits_to_string(&bits[row * self.columns_count()..(row + 1) * self.columns_count()])
+    }
+
+    fn column(&self, bits: &[u8], column: usize) -> String {
+        bits_to_string(&bits.iter().skip(column).step_by(self.columns_count()).copied().collect::<Vec<u8>>())
+    }
+
+    /// Decodes every row or column and flips the corrected bits, returns the number of flips
+    fn decode_lines(&self, bits: &mut [u8], rows: bool) -> usize {
+        let columns_count = self.columns_count();
+        let (lines, code): (usize, &dyn BlockCode) = if rows {
+            (self.rows_count(), &self.row_code)
+        } else {
+            (columns_count, &self.column_code)
+        };
+        let mut flips = 0;
+        for line in 0..lines {
+            let word = if rows { self.row(bits, line) } else { self.column(bits, line) };
+            let result = code.decode(&word);
+            if result.status == DecodeStatus::Uncorrectable {
+                continue;
+            }
+            for position in result.corrected_positions {
+                let index = if rows { line * columns_count + position } else { position * columns_count + line };
+                bits[index] ^= 1;
+                flips += 1;
+            }
+        }
+        flips
+    }
+
+    /// Iterative hard decoding with up to `max_iterations` passes over rows and columns
+    pub fn decode_iterative(&self, codeword: &str, max_iterations: usize) -> ProductDecodeResult {
+        assert_eq!(codeword.len(), self.codeword_bits(), "Product codeword must have {} bits", self.codeword_bits());
+        let received = bits_from_str(codeword);
+        let mut bits = received.clone();
+
+        let mut iterations = 0;
+        while iterations < max_iterations {
+            iterations += 1;
+            let row_flips = self.decode_lines(&mut bits, true);
+            let column_flips = self.decode_lines(&mut bits, false);
+            if row_flips + column_flips == 0 {
+                break;
+            }
+        }
+
+        let mut failed_rows = self.failed_lines(&bits, true);
+        let mut failed_columns = self.failed_lines(&bits, false);
+        if let ([row], [column]) = (failed_rows.as_slice(), failed_columns.as_slice()) {
+            let index = row * self.columns_count() + column;
+            bits[index] ^= 1;
+            failed_rows = self.failed_lines(&bits, true);
+            failed_columns = self.failed_lines(&bits, false);
+            if !failed_rows.is_empty() || !failed_columns.is_empty() {
+                bits[index] ^= 1;
+            }
+        }
+
+        let corrected_positions = bits.iter()
+            .zip(&received)
+            .enumerate()
+            .filter(|(_, (bit, received))| bit != received)
+            .map(|(position, _)| position)
+            .collect::<Vec<usize>>();
+        let status = if !failed_rows.is_empty() || !failed_columns.is_empty() {
+            DecodeStatus::Uncorrectable
+        } else if corrected_positions.is_empty() {
+            DecodeStatus::Clean
+        } else {
+            DecodeStatus::Corrected
+        };
+        ProductDecodeResult { data: self.extract_matrix_data(&bits), corrected_positions, iterations, failed_rows, failed_columns, status }
+    }
+
+    /// Rows or columns which are not codewords
+    fn failed_lines(&self, bits: &[u8], rows: bool) -> Vec<usize> {
+        let lines = if rows { self.rows_count() } else { self.columns_count() };
+        (0..lines).filter(|&line| {
+            let status = if rows {
+                self.row_code.decode(&self.row(bits, line)).status
+            } else {
+                self.column_code.decode(&self.column(bits, line)).status
+            };
+            status != DecodeStatus::Clean
+        }).collect()
+    }
+
+    /// Data read from the matrix as it is: errors left after decoding stay in the data
+    fn extract_matrix_data(&self, bits: &[u8]) -> String {
+        // Столбцы дают k_c кодовых слов строк, из которых берутся данные
+        let column_data = (0..self.columns_count())
+            .map(|column| self.column_code.extract_data(&self.column(bits, column)).into_bytes())
+            .collect::<Vec<Vec<u8>>>();
+        (0..self.column_code.data_bits()).map(|row| {
+            let row_word = column_data.iter().map(|data| data[row] as char).collect::<String>();
+            self.row_code.extract_data(&row_word)
+        }).collect()
+    }
+}
+
+impl ProductCode<SingleParityCode, SingleParityCode> {
+    /// Two-dimensional parity: a parity bit after every row (VRC) and a parity row at the end (LRC)
+    pub fn two_dimensional_parity(rows: usize, columns: usize) -> Self {
+        Self::new(SingleParityCode::new(columns), SingleParityCode::new(rows))
+    }
+}
+
+impl<R: BlockCode, C: BlockCode> BlockCode for ProductCode<R, C> {
+    fn codeword_bits(&self) -> usize { self.row_code.codeword_bits() * self.column_code.codeword_bits() }
+
+    fn data_bits(&self) -> usize { self.row_code.data_bits() * self.column_code.data_bits() }
+
+    fn encode(&self, data: &str) -> String {
+        assert_eq!(data.len(), self.data_bits(), "Product code encodes {} bits", self.data_bits());
+        let row_data_bits = self.row_code.data_bits();
+        let rows = data.as_bytes()
+            .chunks(row_data_bits)
+            .map(|row| self.row_code.encode(std::str::from_utf8(row).unwrap()).into_bytes())
+            .collect::<Vec<Vec<u8>>>();
+        let columns = (0..self.columns_count())
+            .map(|column| {
+                let column_data = rows.iter().map(|row| row[column] as char).collect::<String>();
+                self.column_code.encode(&column_data).into_bytes()
+            })
+            .collect::<Vec<Vec<u8>>>();
+        (0..self.rows_count())
+            .flat_map(|row| columns.iter().map(move |column| column[row] as char))
+            .collect()
+    }
+
+    fn decode(&self, codeword: &str) -> BlockDecodeResult {
+        let result = self.decode_iterative(codeword, DEFAULT_ITERATIONS);
+        BlockDecodeResult { data: result.data, corrected_positions: result.corrected_positions, status: result.status }
+    }
+
+    fn extract_data(&self, codeword: &str) -> String {
+        assert_eq!(codeword.len(), self.codeword_bits(), "Product codeword must have {} bits", self.codeword_bits());
+        self.extract_matrix_data(&bits_from_str(codeword))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame_fix_algos::block_code::LinearBlockCode;
+    use crate::frame_fix_algos::hamming_code::HammingBlockCode;
+    use crate::test_utils::flip_bits;
+
+
+    fn hamming_product() -> ProductCode<LinearBlockCode, LinearBlockCode> {
+        ProductCode::new(LinearBlockCode::hamming(3), LinearBlockCode::hamming(3))
+    }
+
+    #[test]
+    fn two_dimensional_parity_layout() {
+        let code = ProductCode::two_dimensional_parity(2, 3);
+        // 101 | 0, 011 | 0, строка LRC 110 | 0
+        assert_eq!(code.encode("101011"), "101001101100");
+        assert_eq!((code.codeword_bits(), code.data_bits()), (12, 6));
+    }
+
+    #[test]
+    fn rows_and_columns_are_codewords() {
+        let code = hamming_product();
+        let codeword = code.encode("1011001111000101");
+        let bits = bits_from_str(&codeword);
+        assert!(code.failed_lines(&bits, true).is_empty());
+        assert!(code.failed_lines(&bits, false).is_empty());
+        let result = code.decode_iterative(&codeword, 4);
+        assert_eq!(result.data, "1011001111000101");
+        assert_eq!(result.status, DecodeStatus::Clean);
+        assert_eq!(result.iterations, 1);
+    }
+
+    #[test]
+    fn burst_along_a_row_corrected() {
+        let code = hamming_product();
+        let data = "0110100111010010";
+        let codeword = code.encode(data);
+        for start in [0, 10, 21, 42] {
+            let burst = (start..start + 7).collect::<Vec<usize>>();
+            let result = code.decode_iterative(&flip_bits(&codeword, &burst), 4);
+            assert_eq!(result.data, data);
+            assert_eq!(result.corrected_positions, burst);
+            assert_eq!(result.status, DecodeStatus::Corrected);
+        }
+    }
+
+    #[test]
+    fn scattered_errors_corrected_iteratively() {
+        let code = hamming_product();
+        let data = "1111000010100101";
+        let codeword = code.encode(data);
+        // Две ошибки в строке 0 и две в столбце 3: строки и столбцы помогают друг другу
+        let result = code.decode(&flip_bits(&codeword, &[1, 3, 17, 45]));
+        assert_eq!(result.data, data);
+        assert_eq!(result.corrected_positions, vec![1, 3, 17, 45]);
+    }
+
+    #[test]
+    fn product_of_bit_level_hamming_codes() {
+        let code = ProductCode::new(HammingBlockCode::new(7, 4), HammingBlockCode::new(7, 4));
+        assert_eq!((code.codeword_bits(), code.data_bits()), (49, 16));
+        let data = "0110100111010010";
+        let codeword = code.encode(data);
+        // Данные кода Хэмминга на позициях 2, 4, 5, 6: первая строка данных - строка 2
+        assert_eq!(&codeword[14..21], "1100110");
+        let result = code.decode(&flip_bits(&codeword, &[1, 3, 17, 45]));
+        assert_eq!(result.data, data);
+        assert_eq!(result.corrected_positions, vec![1, 3, 17, 45]);
+        assert_eq!(result.status, DecodeStatus::Corrected);
+    }
+
+    #[test]
+    fn residual_errors_left_in_data() {
+        let code = ProductCode::new(HammingBlockCode::new(7, 4), HammingBlockCode::new(7, 4));
+        let data = "0110100111010010";
+        let codeword = code.encode(data);
+        // Без проходов декодера ошибки в битах данных (2, 2) и (4, 4) не исправляются
+        let result = code.decode_iterative(&flip_bits(&codeword, &[16, 32]), 0);
+        assert_eq!(result.status, DecodeStatus::Uncorrectable);
+        assert_eq!((result.failed_rows, result.failed_columns), (vec![2, 4], vec![2, 4]));
+        assert!(result.corrected_positions.is_empty());
+        assert_eq!(result.data, flip_bits(data, &[0, 5]));
+    }
+
+    #[test]
+    fn parity_product_corrects_single_error() {
+        let code = ProductCode::two_dimensional_parity(4, 8);
+        let data = "11001010011100001111111100000001";
+        let codeword = code.encode(data);
+        for position in [0, 13, 26, 44] {
+            let result = code.decode_iterative(&flip_bits(&codeword, &[position]), 4);
+            assert_eq!(result.data, data);
+            assert_eq!(result.corrected_positions, vec![position]);
+            assert_eq!(result.status, DecodeStatus::Corrected);
+        }
+    }
+
+    #[test]
+    fn residual_errors_reported() {
+        let code = ProductCode::two_dimensional_parity(4, 8);
+        let codeword = code.encode(&"0".repeat(32));
+        // Две ошибки в одной строке: чётность строки сходится, ошибаются два столбца
+        let result = code.decode_iterative(&flip_bits(&codeword, &[9, 12]), 4);
+        assert_eq!(result.status, DecodeStatus::Uncorrectable);
+        assert!(result.failed_rows.is_empty());
+        assert_eq!(result.failed_columns, vec![0, 3]);
+        assert!(result.corrected_positions.is_empty());
+    }
+}
@@ -0,0 +1,148 @@
+// Выкалывание (puncturing) кода со скоростью 1/2: из каждой пары выходных бит
+// свёрточного кодера по периодической маске передаются не все биты, что повышает
+// скорость кода. Маски заданы по порождающим полиномам: первая - для выхода
+// G1 = 133, вторая - для выхода G2 = 171, в порядке NASA_GENERATORS. Для 2/3 и
+// 3/4 это маски 802.11 (A = 133, B = 171), выходы которого тоже идут в порядке
+// A, B, поэтому выколотый поток совпадает с 802.11. В DVB-S те же маски
+// (X = 171, Y = 133, 5/6 и 7/8 оттуда), но в паре первым передаётся X - порядок
+// CCSDS_GENERATORS.
+// На приёме выколотые позиции восстанавливаются как стирания (LLR = 0), которые
+// не влияют на метрики ветвей декодера Витерби.
+
+use crate::utils::convert_char_bit_to_int;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodeRate {
+    Rate1_2,
+    Rate2_3,
+    Rate3_4,
+    Rate5_6,
+    Rate7_8,
+}
+
+impl CodeRate {
+    /// Puncturing masks (G1 = 133 output, G2 = 171 output) of each pair, '1' means transmitted
+    pub fn pattern(&self) -> (&'static str, &'static str) {
+        match self {
+            CodeRate::Rate1_2 => ("1", "1"),
+            CodeRate::Rate2_3 => ("11", "10"),
+            CodeRate::Rate3_4 => ("110", "101"),
+            CodeRate::Rate5_6 => ("11010", "10101"),
+            CodeRate::Rate7_8 => ("1111010", "1000101"),
+        }
+    }
+
+    fn is_transmitted(&self, pair_index: usize, output_index: usize) -> bool {
+        let (first_mask, second_mask) = self.pattern();
+        let mask = if output_index == 0 { first_mask } else { second_mask };
+        mask.as_bytes()[pair_index % mask.len()] == b'1'
+    }
+}
+
+
+/// Drop the masked bits from a rate 1/2 encoded frame
+pub fn puncture(encoded_frame: &str, rate: CodeRate) -> String {
+    encoded_frame
+        .chars()
+        .enumerate()
+        .filter(|(index, _)| rate.is_transmitted(index / 2, index % 2))
+        .map(|(_, bit)| bit)
+        .collect()
+}
+
+
+/// Restore the rate 1/2 layout of received LLRs, inserting erasures (0.0) at punctured positions.
+/// A pair that is cut off by the end of the frame is dropped.
+pub fn depuncture_soft(llrs: &[f64], rate: CodeRate) -> Vec<f64> {
+    let mut received = llrs.iter();
+    let mut result = Vec::new();
+    let mut pair_index = 0;
+    loop {
+        let mut pair = [0.0; 2];
+        for (output_index, value) in pair.iter_mut().enumerate() {
+            if rate.is_transmitted(pair_index, output_index) {
+                match received.next() {
+                    Some(&llr) => *value = llr,
+                    None => return result,
+                }
+            }
+        }
+        result.extend_from_slice(&pair);
+        pair_index += 1;
+    }
+}
+
+
+/// Restore the rate 1/2 layout of received hard bits as LLRs (+1 for 0, -1 for 1, 0 for erasures)
+pub fn depuncture(frame: &str, rate: CodeRate) -> Vec<f64> {
+    let llrs = frame.chars()
+        .map(|bit| if convert_char_bit_to_int(bit) == 0 { 1.0 } else { -1.0 })
+        .collect::<Vec<f64>>();
+    depuncture_soft(&llrs, rate)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame_fix_algos::nasa_convo_code::encode;
+    use crate::frame_fix_algos::state_machine::BitSM;
+
+    #[test]
+    fn frame_punctured_3_4() {
+        // Пары: A0B0 A1B1 A2B2 -> передаются A0 B0 A1 B2, как в 802.11
+        assert_eq!(puncture("101101", CodeRate::Rate3_4), "1011");
+        assert_eq!(puncture("111001011100", CodeRate::Rate3_4), "11110110");
+    }
+
+    #[test]
+    fn impulse_response_punctured_as_in_802_11() {
+        let mut bit_sm = BitSM::new();
+        let states_map = bit_sm.states_map();
+        let encoded_frame = encode("1000000".to_string(), &mut bit_sm, &states_map);
+        // Отклик на единицу: A = 133 -> 1011011, B = 171 -> 1111001
+        assert_eq!(encoded_frame, "11011111001011");
+        // 802.11 3/4: A0 B0 A1 B2 A3 B3 A4 B5 A6 B6
+        assert_eq!(puncture(&encoded_frame, CodeRate::Rate3_4), "1101110011");
+        // 802.11 2/3: A0 B0 A1 A2 B2 A3 A4 B4 A5 A6 B6
+        assert_eq!(puncture(&encoded_frame, CodeRate::Rate2_3), "11011100111");
+    }
+
+    #[test]
+    fn rate_1_2_left_untouched() {
+        assert_eq!(puncture("111001011100", CodeRate::Rate1_2), "111001011100");
+        assert_eq!(depuncture("10", CodeRate::Rate1_2), vec![-1.0, 1.0]);
+    }
+
+    #[test]
+    fn punctured_lengths_match_rates() {
+        let encoded_frame = "10".repeat(420);
+        for (rate, numerator, denominator) in [
+            (CodeRate::Rate2_3, 2, 3),
+            (CodeRate::Rate3_4, 3, 4),
+            (CodeRate::Rate5_6, 5, 6),
+            (CodeRate::Rate7_8, 7, 8),
+        ] {
+            let punctured = puncture(&encoded_frame, rate);
+            assert_eq!(punctured.len() * numerator, 420 * denominator);
+        }
+    }
+
+    #[test]
+    fn erasures_inserted_on_depuncture() {
+        assert_eq!(
+            depuncture("1011", CodeRate::Rate3_4),
+            vec![-1.0, 1.0, -1.0, 0.0, 0.0, -1.0]
+        );
+        assert_eq!(
+            depuncture_soft(&[0.5, -0.5, 0.25], CodeRate::Rate2_3),
+            vec![0.5, -0.5, 0.25, 0.0]
+        );
+    }
+
+    #[test]
+    fn partial_pair_dropped() {
+        assert_eq!(depuncture("1", CodeRate::Rate1_2), Vec::<f64>::new());
+    }
+}
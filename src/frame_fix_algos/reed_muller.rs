@@ -0,0 +1,287 @@
+// Коды Рида-Маллера RM(r, m): длина n = 2^m, k = C(m, 0) + ... + C(m, r) бит
+// данных, минимальное расстояние 2^(m-r). Кодовое слово - таблица значений
+// булевого полинома степени не выше r от m переменных на всех 2^m точках.
+// Строки порождающей матрицы - одночлены x_S = произведение x_i, i из S,
+// упорядоченные по степени, внутри степени - лексикографически. В точке j
+// переменная x_i (i от 0) равна биту m-1-i числа j, так x_0 = 0...01...1.
+//
+// Мажоритарное декодирование Рида: коэффициент одночлена x_S старшей
+// оставшейся степени d равен сумме принятых бит по любой грани, где
+// переменные не из S фиксированы, - это 2^(m-d) независимых проверок,
+// решение принимается большинством. Найденные одночлены степени d вычитаются
+// из принятого слова, и декодирование переходит к степени d - 1.
+// Ничья при голосовании означает, что ошибок не меньше d_min / 2 - ошибка
+// только обнаруживается.
+//
+// Для RM(1, m) мягкое декодирование по максимуму правдоподобия: быстрое
+// преобразование Адамара LLR даёт корреляцию со всеми 2^m линейными функциями
+// сразу, наибольшее по модулю значение определяет линейную часть, его знак -
+// свободный член.
+
+use crate::frame_fix_algos::block_code::BlockCode;
+use crate::frame_fix_algos::decode_report::{BlockDecodeResult, DecodeStatus};
+use crate::frame_fix_algos::viterbi::SoftSymbols;
+use crate::utils::{bits_from_str, bits_to_string};
+
+const MAX_VARIABLES: usize = 20;
+
+
+pub struct ReedMullerCode {
+    order: usize,
+    variables: usize,
+    /// Variables of every generator row monomial
+    monomials: Vec<Vec<usize>>,
+    generator: Vec<Vec<u8>>,
+}
+
+impl ReedMullerCode {
+    /// RM(r, m) code of length 2^m with polynomials of degree up to r
+    pub fn new(order: usize, variables: usize) -> Self {
+        assert!((1..=MAX_VARIABLES).contains(&variables), "Number of variables must be from 1 to {MAX_VARIABLES}");
+        assert!(order <= variables, "Order can't exceed the number of variables");
+        let mut monomials = Vec::new();
+        for degree in 0..=order {
+            push_monomials(variables, degree, 0, &mut Vec::new(), &mut monomials);
+        }
+        let generator = monomials.iter()
+            .map(|monomial| (0..1usize << variables).map(|point| evaluate(monomial, variables, point)).collect())
+            .collect();
+        ReedMullerCode { order, variables, monomials, generator }
+    }
+
+    pub fn order(&self) -> usize { self.order }
+
+    pub fn variables(&self) -> usize { self.variables }
+
+    pub fn minimum_distance(&self) -> usize { 1 << (self.variables - self.order) }
+
+    pub fn generator_matrix(&self) -> Vec<String> {
+        self.generator.iter().map(|row| bits_to_string(row)).collect()
+    }
+
+    fn encode_bits(&self, data: &[u8]) -> Vec<u8> {
+        let mut codeword = vec![0u8; self.codeword_bits()];
+        for (row, _) in self.generator.iter().zip(data).filter(|(_, &bit)| bit == 1) {
+            for (bit, &row_bit) in codeword.iter_mut().zip(row) {
+                *bit ^= row_bit;
+            }
+        }
+        codeword
+    }
+
+    /// Check sums over every face where the variables outside `monomial` are fixed
+    fn majority_vote(&self, monomial: &[usize], residual: &[u8]) -> Option<u8> {
+        let free_variables = (0..self.variables).filter(|variable| !monomial.contains(variable)).collect::<Vec<usize>>();
+        let mut check_sums = vec![0u8; 1 << free_variables.len()];
+        for (point, &bit) in residual.iter().enumerate() {
+            let face = free_variables.iter()
+                .fold(0, |face, &variable| (face << 1) | evaluate(&[variable], self.variables, point) as usize);
+            check_sums[face] ^= bit;
+        }
+        let ones = check_sums.iter().filter(|&&sum| sum == 1).count();
+        match (2 * ones).cmp(&check_sums.len()) {
+            std::cmp::Ordering::Greater => Some(1),
+            std::cmp::Ordering::Less => Some(0),
+            std::cmp::Ordering::Equal => None,
+        }
+    }
+
+    /// Reed majority-logic decoding, corrects up to 2^(m-r-1) - 1 errors
+    pub fn decode_majority(&self, codeword: &str) -> BlockDecodeResult {
+        assert_eq!(codeword.len(), self.codeword_bits(), "Reed-Muller codeword must have {} bits", self.codeword_bits());
+        let received = bits_from_str(codeword);
+        let mut residual = received.clone();
+        let mut data = vec![0u8; self.monomials.len()];
+        let mut tie = false;
+
+        for degree in (0..=self.order).rev() {
+            let indexes = (0..self.monomials.len())
+                .filter(|&index| self.monomials[index].len() == degree)
+                .collect::<Vec<usize>>();
+            for &index in &indexes {
+                data[index] = self.majority_vote(&self.monomials[index], &residual).unwrap_or_else(|| {
+                    tie = true;
+                    0
+                });
+            }
+            for &index in indexes.iter().filter(|&&index| data[index] == 1) {
+                for (bit, &row_bit) in residual.iter_mut().zip(&self.generator[index]) {
+                    *bit ^= row_bit;
+                }
+            }
+        }
+
+        if tie {
+            return BlockDecodeResult {
+                data: bits_to_string(&data),
+                corrected_positions: Vec::new(),
+                status: DecodeStatus::Uncorrectable,
+            };
+        }
+        self.result(data, &received)
+    }
+
+    /// Soft maximum likelihood decoding of a first order code via the fast Hadamard transform
+    pub fn decode_soft(&self, symbols: &SoftSymbols) -> BlockDecodeResult {
+        assert_eq!(self.order, 1, "Hadamard transform decoding is defined for first order codes");
+        let mut spectrum = symbols.to_llrs();
+        assert_eq!(spectrum.len(), self.codeword_bits(), "Reed-Muller codeword must have {} symbols", self.codeword_bits());
+        let received = spectrum.iter().map(|&llr| (llr < 0.0) as u8).collect::<Vec<u8>>();
+
+        // Бабочки преобразования Адамара: spectrum[u] = sum llr[j] * (-1)^(u * j)
+        let mut half = 1;
+        while half < spectrum.len() {
+            for block in spectrum.chunks_exact_mut(2 * half) {
+                let (low, high) = block.split_at_mut(half);
+                for (first, second) in low.iter_mut().zip(high.iter_mut()) {
+                    (*first, *second) = (*first + *second, *first - *second);
+                }
+            }
+            half *= 2;
+        }
+
+        let (best, correlation) = spectrum.iter()
+            .enumerate()
+            .max_by(|(_, first), (_, second)| first.abs().total_cmp(&second.abs()))
+            .map(|(index, &value)| (index, value))
+            .unwrap();
+        // Положительный LLR - ноль, поэтому отрицательная корреляция - инверсия (свободный член 1)
+        let mut data = vec![(correlation < 0.0) as u8];
+        data.extend((0..self.variables).map(|variable| evaluate(&[variable], self.variables, best)));
+        self.result(data, &received)
+    }
+
+    fn result(&self, data: Vec<u8>, received: &[u8]) -> BlockDecodeResult {
+        let corrected_positions = self.encode_bits(&data).iter()
+            .zip(received)
+            .enumerate()
+            .filter(|(_, (decoded, received))| decoded != received)
+            .map(|(position, _)| position)
+            .collect::<Vec<usize>>();
+        BlockDecodeResult {
+            data: bits_to_string(&data),
+            status: if corrected_positions.is_empty() { DecodeStatus::Clean } else { DecodeStatus::Corrected },
+            corrected_positions,
+        }
+    }
+}
+
+impl BlockCode for ReedMullerCode {
+    fn codeword_bits(&self) -> usize { 1 << self.variables }
+
+    fn data_bits(&self) -> usize { self.monomials.len() }
+
+    fn encode(&self, data: &str) -> String {
+        assert_eq!(data.len(), self.data_bits(), "Reed-Muller code encodes {} bits", self.data_bits());
+        bits_to_string(&self.encode_bits(&bits_from_str(data)))
+    }
+
+    fn decode(&self, codeword: &str) -> BlockDecodeResult {
+        self.decode_majority(codeword)
+    }
+}
+
+
+fn push_monomials(variables: usize, degree: usize, start: usize, current: &mut Vec<usize>, monomials: &mut Vec<Vec<usize>>) {
+    if current.len() == degree {
+        monomials.push(current.clone());
+        return;
+    }
+    for variable in start..variables {
+        current.push(variable);
+        push_monomials(variables, degree, variable + 1, current, monomials);
+        current.pop();
+    }
+}
+
+/// Value of the product of `monomial` variables at a point
+fn evaluate(monomial: &[usize], variables: usize, point: usize) -> u8 {
+    monomial.iter().all(|&variable| (point >> (variables - 1 - variable)) & 1 == 1) as u8
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame_fix_algos::code_analysis::analyze;
+    use crate::test_utils::{bpsk_llrs, flip_bits};
+
+
+    #[test]
+    fn generator_matrix_of_rm_1_3_and_rm_2_3() {
+        let code = ReedMullerCode::new(1, 3);
+        assert_eq!(code.generator_matrix(), vec!["11111111", "00001111", "00110011", "01010101"]);
+        let code = ReedMullerCode::new(2, 3);
+        assert_eq!(code.data_bits(), 7);
+        assert_eq!(&code.generator_matrix()[4..], &["00000011", "00000101", "00010001"]);
+    }
+
+    #[test]
+    fn minimum_distance_matches_enumeration() {
+        for (order, variables) in [(1, 3), (1, 4), (2, 4), (1, 5), (2, 5)] {
+            let code = ReedMullerCode::new(order, variables);
+            assert_eq!(analyze(&code).minimum_distance, code.minimum_distance());
+        }
+    }
+
+    #[test]
+    fn majority_logic_corrects_up_to_half_distance() {
+        // RM(1, 5): (32, 6, 16), исправляет 7 ошибок
+        let code = ReedMullerCode::new(1, 5);
+        let codeword = code.encode("101101");
+        for errors in [vec![0, 1, 2, 3, 4, 5, 6], vec![3, 8, 13, 18, 23, 28, 31], vec![31]] {
+            let result = code.decode_majority(&flip_bits(&codeword, &errors));
+            assert_eq!(result.data, "101101");
+            assert_eq!(result.corrected_positions, errors);
+            assert_eq!(result.status, DecodeStatus::Corrected);
+        }
+        assert_eq!(code.decode(&codeword).status, DecodeStatus::Clean);
+    }
+
+    #[test]
+    fn second_order_code_corrects_single_errors() {
+        let code = ReedMullerCode::new(2, 4);
+        let data = "10110011101";
+        let codeword = code.encode(data);
+        for position in 0..16 {
+            let result = code.decode(&flip_bits(&codeword, &[position]));
+            assert_eq!(result.data, data);
+            assert_eq!(result.corrected_positions, vec![position]);
+        }
+    }
+
+    #[test]
+    fn tie_reported_as_uncorrectable() {
+        let code = ReedMullerCode::new(1, 3);
+        let codeword = code.encode("0110");
+        let result = code.decode_majority(&flip_bits(&codeword, &[0, 1]));
+        assert_eq!(result.status, DecodeStatus::Uncorrectable);
+        assert!(result.corrected_positions.is_empty());
+    }
+
+    #[test]
+    fn hadamard_decoding_beats_hard_decisions() {
+        let code = ReedMullerCode::new(1, 4);
+        let codeword = code.encode("11010");
+        // 5 слабых ошибок - больше, чем исправляет жёсткое декодирование (3)
+        let errors = [1, 4, 6, 11, 15];
+        let llrs = bpsk_llrs(&codeword, |index| if errors.contains(&index) { -0.5 } else { 2.0 });
+
+        let result = code.decode_soft(&SoftSymbols::Llr(llrs.clone()));
+        assert_eq!(result.data, "11010");
+        assert_eq!(result.corrected_positions, errors);
+
+        let hard = llrs.iter().map(|&llr| if llr < 0.0 { '1' } else { '0' }).collect::<String>();
+        assert_ne!(code.decode_majority(&hard).data, "11010");
+    }
+
+    #[test]
+    fn hadamard_decoding_of_quantized_symbols() {
+        let code = ReedMullerCode::new(1, 3);
+        let codeword = code.encode("1001");
+        let values = codeword.chars().map(|bit| if bit == '0' { 1 } else { 6 }).collect();
+        let result = code.decode_soft(&SoftSymbols::Quantized { bits: 3, values });
+        assert_eq!(result.data, "1001");
+        assert_eq!(result.status, DecodeStatus::Clean);
+    }
+}
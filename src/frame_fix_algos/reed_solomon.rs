@@ -0,0 +1,267 @@
+// Код Рида-Соломона над GF(2^8). Кодовое слово - n байт, первые k из которых
+// данные, остальные 2t = n - k - проверочные (систематический код). Порождающий
+// полином g(x) = (x - b^fcr)(x - b^(fcr+1))..(x - b^(fcr+2t-1)), где b = a^step,
+// a - примитивный элемент поля. Байт с индексом 0 - старший коэффициент.
+// Декодирование: синдромы, Берлекэмп-Месси (с учётом стираний), поиск Ченя для
+// корней локатора ошибок и алгоритм Форни для значений ошибок. Код исправляет
+// любую комбинацию из e ошибок и s стираний при 2e + s <= 2t.
+// Укороченный код (n < 255) получается отбрасыванием старших нулевых байтов.
+
+use crate::galois::{berlekamp_massey, GaloisField, GfPolynomial};
+
+const FIELD_ORDER: usize = 255;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RsError {
+    /// Codeword or data block has the wrong number of bytes
+    WrongLength,
+    /// More errors and erasures than the code can correct
+    TooManyErrors,
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RsDecodeResult {
+    pub data: Vec<u8>,
+    /// Indexes of corrected bytes in the codeword, including corrected erasures
+    pub corrected_positions: Vec<usize>,
+}
+
+
+pub struct ReedSolomon {
+    codeword_len: usize,
+    data_len: usize,
+    first_root: usize,
+    root_step: usize,
+    field: GaloisField,
+    generator: GfPolynomial,
+}
+
+impl ReedSolomon {
+    /// RS(n, k) over GF(256) defined by `field_polynomial` (with the x^8 term, e.g. 0x11d).
+    /// Generator roots are b^first_root .. b^(first_root + n - k - 1) with b = a^root_step.
+    pub fn new(codeword_len: usize, data_len: usize, field_polynomial: u16, first_root: usize, root_step: usize) -> Self {
+        assert!(codeword_len <= FIELD_ORDER, "Codeword can't be longer than {FIELD_ORDER} bytes");
+        assert!(data_len > 0 && data_len < codeword_len, "Data must be shorter than the codeword");
+        assert!(gcd(root_step, FIELD_ORDER) == 1, "Root step must be coprime with {FIELD_ORDER}");
+
+        let field = GaloisField::new(8, field_polynomial as u32);
+        let generator = (0..codeword_len - data_len).fold(GfPolynomial::one(), |generator, root_index| {
+            let root = field.exp(((first_root + root_index) * root_step) as i64);
+            generator.multiply(&GfPolynomial::new(vec![root, 1]), &field)
+        });
+
+        ReedSolomon { codeword_len, data_len, first_root, root_step, field, generator }
+    }
+
+    /// CCSDS 131.0-B RS(255, 223), conventional (not dual) basis representation
+    pub fn ccsds() -> Self { Self::new(255, 223, 0x187, 112, 11) }
+
+    /// DVB RS(204, 188), shortened from RS(255, 239)
+    pub fn dvb() -> Self { Self::new(204, 188, 0x11d, 0, 1) }
+
+    pub fn codeword_len(&self) -> usize { self.codeword_len }
+
+    pub fn data_len(&self) -> usize { self.data_len }
+
+    fn parity_len(&self) -> usize { self.codeword_len - self.data_len }
+
+    pub fn encode(&self, data: &[u8]) -> Result<Vec<u8>, RsError> {
+        if data.len() != self.data_len {
+            return Err(RsError::WrongLength);
+        }
+        // Проверочные байты - остаток от деления data(x) * x^2t на g(x)
+        let message = GfPolynomial::new(data.iter().rev().map(|&byte| byte as u16).collect());
+        let (_, remainder) = message.shift(self.parity_len()).div_rem(&self.generator, &self.field);
+        let mut codeword = data.to_vec();
+        codeword.extend((0..self.parity_len()).rev().map(|degree| remainder.coefficient(degree) as u8));
+        Ok(codeword)
+    }
+
+    /// b^degree, the locator of the byte at degree `degree`
+    fn locator(&self, degree: usize) -> u16 {
+        self.field.exp((degree * self.root_step) as i64)
+    }
+
+    fn syndromes(&self, codeword: &[u8]) -> Vec<u16> {
+        (0..self.parity_len()).map(|index| {
+            let root = self.field.exp(((self.first_root + index) * self.root_step) as i64);
+            codeword.iter().fold(0, |result, &byte| self.field.multiply(result, root) ^ byte as u16)
+        }).collect()
+    }
+
+    /// Decode a received codeword. `erasures` are indexes of bytes known to be unreliable.
+    pub fn decode(&self, received: &[u8], erasures: &[usize]) -> Result<RsDecodeResult, RsError> {
+        if received.len() != self.codeword_len || erasures.iter().any(|&index| index >= self.codeword_len) {
+            return Err(RsError::WrongLength);
+        }
+        if erasures.len() > self.parity_len() {
+            return Err(RsError::TooManyErrors);
+        }
+        let syndromes = self.syndromes(received);
+        if syndromes.iter().all(|&syndrome| syndrome == 0) {
+            return Ok(RsDecodeResult { data: received[..self.data_len].to_vec(), corrected_positions: Vec::new() });
+        }
+
+        let error_locator = self.error_locator(&syndromes, erasures);
+        let errors_count = error_locator.degree().unwrap_or(0);
+
+        // Поиск Ченя: корни локатора - обратные к локаторам ошибочных байтов
+        let error_degrees = (0..self.codeword_len)
+            .filter(|&degree| error_locator.evaluate(self.field.inverse(self.locator(degree)), &self.field) == 0)
+            .collect::<Vec<usize>>();
+        if error_degrees.len() != errors_count {
+            return Err(RsError::TooManyErrors);
+        }
+
+        // Omega(x) = S(x) * Lambda(x) mod x^2t
+        let evaluator = GfPolynomial::new(syndromes)
+            .multiply(&error_locator, &self.field)
+            .truncate(self.parity_len());
+        let locator_derivative = error_locator.derivative();
+
+        let mut corrected = received.to_vec();
+        let mut corrected_positions = Vec::with_capacity(errors_count);
+        for &degree in &error_degrees {
+            let inverse = self.field.inverse(self.locator(degree));
+            let denominator = locator_derivative.evaluate(inverse, &self.field);
+            if denominator == 0 {
+                return Err(RsError::TooManyErrors);
+            }
+            let scale = self.field.exp((degree * self.root_step) as i64 * (1 - self.first_root as i64));
+            let magnitude = self.field.multiply(
+                scale,
+                self.field.divide(evaluator.evaluate(inverse, &self.field), denominator)
+            );
+            let position = self.codeword_len - 1 - degree;
+            corrected[position] ^= magnitude as u8;
+            corrected_positions.push(position);
+        }
+
+        if self.syndromes(&corrected).iter().any(|&syndrome| syndrome != 0) {
+            return Err(RsError::TooManyErrors);
+        }
+        corrected_positions.sort_unstable();
+        Ok(RsDecodeResult { data: corrected[..self.data_len].to_vec(), corrected_positions })
+    }
+
+    /// Berlekamp-Massey started from the erasure locator
+    fn error_locator(&self, syndromes: &[u16], erasures: &[usize]) -> GfPolynomial {
+        let erasure_locator = erasures.iter().fold(GfPolynomial::one(), |locator, &position| {
+            let erasure_locator = self.locator(self.codeword_len - 1 - position);
+            locator.multiply(&GfPolynomial::new(vec![1, erasure_locator]), &self.field)
+        });
+        berlekamp_massey(syndromes, erasure_locator, erasures.len(), &self.field)
+    }
+}
+
+
+fn gcd(first: usize, second: usize) -> usize {
+    if second == 0 { first } else { gcd(second, first % second) }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_data(len: usize) -> Vec<u8> {
+        (0..len).map(|index| (index * 37 + 11) as u8).collect()
+    }
+
+    fn corrupt(codeword: &[u8], positions: &[usize]) -> Vec<u8> {
+        let mut corrupted = codeword.to_vec();
+        for (shift, &position) in positions.iter().enumerate() {
+            corrupted[position] ^= (shift as u8).wrapping_mul(29).wrapping_add(1) | 1;
+        }
+        corrupted
+    }
+
+    #[test]
+    fn generator_polynomial_built() {
+        let reed_solomon = ReedSolomon::new(255, 253, 0x11d, 0, 1);
+        // (x - 1)(x - a) = x^2 + 3x + 2
+        assert_eq!(reed_solomon.generator.coefficients(), &[2, 3, 1]);
+    }
+
+    #[test]
+    fn known_codeword_encoded() {
+        let reed_solomon = ReedSolomon::new(21, 11, 0x11d, 0, 1);
+        let codeword = reed_solomon.encode(b"hello world").unwrap();
+        assert_eq!(&codeword[11..], b"\xed%T\xc4\xfd\xfd\x89\xf3\xa8\xaa");
+    }
+
+    #[test]
+    fn ccsds_errors_corrected() {
+        let reed_solomon = ReedSolomon::ccsds();
+        let data = test_data(223);
+        let codeword = reed_solomon.encode(&data).unwrap();
+        assert_eq!(reed_solomon.syndromes(&codeword), vec![0; 32]);
+
+        let positions = (0..16).map(|index| index * 15 + 3).collect::<Vec<usize>>();
+        let result = reed_solomon.decode(&corrupt(&codeword, &positions), &[]).unwrap();
+        assert_eq!(result.data, data);
+        assert_eq!(result.corrected_positions, positions);
+    }
+
+    #[test]
+    fn ccsds_errors_and_erasures_corrected() {
+        let reed_solomon = ReedSolomon::ccsds();
+        let data = test_data(223);
+        let codeword = reed_solomon.encode(&data).unwrap();
+
+        // 2 * 10 ошибок + 12 стираний = 32
+        let error_positions = (0..10).map(|index| index * 20).collect::<Vec<usize>>();
+        let erasures = (0..12).map(|index| index * 20 + 7).collect::<Vec<usize>>();
+        let mut corrupted = corrupt(&codeword, &error_positions);
+        for &position in &erasures[..6] {
+            corrupted[position] = 0;
+        }
+        let result = reed_solomon.decode(&corrupted, &erasures).unwrap();
+        assert_eq!(result.data, data);
+
+        let only_erasures = (100..132).collect::<Vec<usize>>();
+        let result = reed_solomon.decode(&corrupt(&codeword, &only_erasures), &only_erasures).unwrap();
+        assert_eq!(result.data, data);
+        assert_eq!(result.corrected_positions, only_erasures);
+    }
+
+    #[test]
+    fn too_many_errors_reported() {
+        let reed_solomon = ReedSolomon::ccsds();
+        let codeword = reed_solomon.encode(&test_data(223)).unwrap();
+        let positions = (0..17).map(|index| index * 13).collect::<Vec<usize>>();
+        assert_eq!(reed_solomon.decode(&corrupt(&codeword, &positions), &[]), Err(RsError::TooManyErrors));
+        let erasures = (0..33).collect::<Vec<usize>>();
+        assert_eq!(reed_solomon.decode(&codeword, &erasures), Err(RsError::TooManyErrors));
+    }
+
+    #[test]
+    fn dvb_shortened_code_corrects_8_errors() {
+        let reed_solomon = ReedSolomon::dvb();
+        let data = test_data(188);
+        let codeword = reed_solomon.encode(&data).unwrap();
+        assert_eq!(codeword.len(), 204);
+
+        let positions = [0, 1, 2, 50, 100, 187, 188, 203];
+        let result = reed_solomon.decode(&corrupt(&codeword, &positions), &[]).unwrap();
+        assert_eq!(result.data, data);
+        assert_eq!(result.corrected_positions, positions.to_vec());
+    }
+
+    #[test]
+    fn wrong_lengths_rejected() {
+        let reed_solomon = ReedSolomon::dvb();
+        assert_eq!(reed_solomon.encode(&[0; 10]), Err(RsError::WrongLength));
+        assert_eq!(reed_solomon.decode(&[0; 10], &[]), Err(RsError::WrongLength));
+        assert_eq!(reed_solomon.decode(&[0; 204], &[204]), Err(RsError::WrongLength));
+    }
+
+    #[test]
+    #[should_panic(expected = "Polynomial 0b100011011 is not primitive")]
+    fn non_primitive_polynomial_rejected() {
+        // Неприводимый, но не примитивный полином AES
+        ReedSolomon::new(255, 223, 0x11b, 0, 1);
+    }
+}
@@ -1,13 +1,52 @@
 use std::collections::HashMap;
 use crate::utils::convert_char_bit_to_int;
 
+// Свёрточный кодер со скоростью 1/n: регистр из K-1 ячеек и n порождающих
+// полиномов в восьмеричной записи. Старший бит полинома соответствует входному
+// биту, следующие - ячейкам регистра от самой новой к самой старой.
+// Например, NASA K=7: 133 = 1 011 011 -> вход и ячейки 1, 2, 4, 5.
+
+pub const NASA_GENERATORS: [u32; 2] = [0o133, 0o171];
+pub const GSM_GENERATORS: [u32; 2] = [0o23, 0o33];
+// CCSDS 131.0-B: тот же код, что NASA, но выход 171 идёт первым, а выход 133 инвертируется
+pub const CCSDS_GENERATORS: [u32; 2] = [0o171, 0o133];
+pub const LTE_GENERATORS: [u32; 3] = [0o133, 0o171, 0o165];
+
 pub struct BitSM {
     registers: String,
+    control_indexes: Vec<Vec<usize>>,
+}
+
+impl Default for BitSM {
+    fn default() -> Self { Self::new() }
 }
 
 impl BitSM {
 
-    pub fn new() -> Self { BitSM { registers: String::from("000000") } }
+    /// NASA K=7 rate 1/2 code with generators 133 and 171
+    pub fn new() -> Self { Self::with_generators(7, &NASA_GENERATORS) }
+
+    /// Plain shift register without output taps
+    pub fn from_state(registers: &str) -> Self {
+        BitSM { registers: registers.to_string(), control_indexes: Vec::new() }
+    }
+
+    /// Rate 1/n code of the given constraint length with octal generator polynomials
+    pub fn with_generators(constraint_length: usize, generators: &[u32]) -> Self {
+        assert!(constraint_length >= 2, "Constraint length must be at least 2");
+        assert!(!generators.is_empty(), "At least one generator polynomial is required");
+        let control_indexes = generators.iter().map(|&generator| {
+            assert!(
+                generator < (1 << constraint_length),
+                "Generator {generator:o} is longer than constraint length {constraint_length}"
+            );
+            // Индекс 0 - входной бит, индекс i > 0 - ячейка регистра i - 1
+            (0..constraint_length)
+                .filter(|index| (generator >> (constraint_length - 1 - index)) & 1 == 1)
+                .collect()
+        }).collect();
+        BitSM { registers: "0".repeat(constraint_length - 1), control_indexes }
+    }
 
     fn build_empty_states_map(&self) -> HashMap<String, HashMap<String, String>> {
         let k = self.get_register_size() + 1;
@@ -26,48 +65,40 @@ impl BitSM {
         let mut empty_states_map = self.build_empty_states_map();
         for (key, val) in &mut empty_states_map {
             let zero_transition = format!("{}{}", "0", key);
-            let (first_control_bit, second_control_bit) = self.get_next_control_bits('0', key);
-            val.insert(zero_transition[..key.len()].to_string(), format!("{first_control_bit}{second_control_bit}"));
+            val.insert(zero_transition[..key.len()].to_string(), self.get_next_control_bits('0', key));
 
             let one_transition = format!("{}{}", "1", key);
-            let (first_control_bit, second_control_bit) = self.get_next_control_bits('1', key);
-            val.insert(one_transition[0..key.len()].to_string(), format!("{first_control_bit}{second_control_bit}"));
+            val.insert(one_transition[0..key.len()].to_string(), self.get_next_control_bits('1', key));
         }
         empty_states_map
     }
 
-
-    fn get_first_control_bit(&self, next_bit: char, state: &String) -> usize {
-        let index_sequence = vec![1, 2, 4, 5];
-        self.sum_control_bits(index_sequence, next_bit, &state)
-    }
-
-    fn sum_control_bits(&self, indexes: Vec<usize>, next_bit: char, state: &String) -> usize {
+    fn sum_control_bits(&self, indexes: &[usize], next_bit: char, state: &str) -> usize {
         let mut transition = 0;
-        transition ^= convert_char_bit_to_int(next_bit);
-        for index in indexes {
-            let x = convert_char_bit_to_int(state.as_bytes()[index] as char);
+        for &index in indexes {
+            let x = if index == 0 {
+                convert_char_bit_to_int(next_bit)
+            } else {
+                convert_char_bit_to_int(state.as_bytes()[index - 1] as char)
+            };
             transition ^= x;
         }
         transition
     }
 
-    fn get_second_control_bit(&self, next_bit: char, state: &String) -> usize {
-        let index_sequence = vec![0, 1, 2, 5];
-        self.sum_control_bits(index_sequence, next_bit, state)
-    }
-
-    fn get_next_control_bits(&self, next_bit: char, state: &String) -> (usize, usize) {
-        let (first_bit, second_bit) = (
-            self.get_first_control_bit(next_bit, &state),
-            self.get_second_control_bit(next_bit, &state)
-        );
-
-        (first_bit, second_bit)
+    fn get_next_control_bits(&self, next_bit: char, state: &str) -> String {
+        self.control_indexes
+            .iter()
+            .map(|indexes| self.sum_control_bits(indexes, next_bit, state))
+            .map(|bit| if bit == 1 { '1' } else { '0' })
+            .collect()
     }
 
     pub fn get_register_size(&self) -> usize { self.registers.len() }
 
+    /// Number of output bits per input bit
+    pub fn get_output_size(&self) -> usize { self.control_indexes.len() }
+
     pub fn shift_register(&mut self, next_bit: char) {
         self.registers = format!("{}{}", next_bit, &self.registers[..self.registers.len() - 1]);
     }
@@ -76,3 +107,46 @@ impl BitSM {
         &self.registers
     }
 }
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nasa_transitions_kept() {
+        let states_map = BitSM::new().states_map();
+        assert_eq!(states_map["000000"]["100000"], "11");
+        assert_eq!(states_map["100000"]["010000"], "01");
+        assert_eq!(states_map["110000"]["111000"], "01");
+    }
+
+    #[test]
+    fn generators_converted_to_taps() {
+        let bit_sm = BitSM::with_generators(7, &NASA_GENERATORS);
+        assert_eq!(bit_sm.control_indexes, vec![vec![0, 2, 3, 5, 6], vec![0, 1, 2, 3, 6]]);
+        assert_eq!(bit_sm.get_register_size(), 6);
+        assert_eq!(bit_sm.get_output_size(), 2);
+    }
+
+    #[test]
+    fn rate_1_3_states_map() {
+        let bit_sm = BitSM::with_generators(7, &LTE_GENERATORS);
+        let states_map = bit_sm.states_map();
+        assert_eq!(states_map.len(), 64);
+        assert_eq!(states_map["000000"]["100000"], "111");
+        assert_eq!(states_map["000000"]["000000"], "000");
+    }
+
+    #[test]
+    #[should_panic(expected = "Generator 17 is longer than constraint length 3")]
+    fn generator_longer_than_constraint_length() {
+        BitSM::with_generators(3, &[0o17, 0o5]);
+    }
+
+    #[test]
+    #[should_panic(expected = "At least one generator polynomial is required")]
+    fn generators_required() {
+        BitSM::with_generators(3, &[]);
+    }
+}
@@ -0,0 +1,76 @@
+use std::collections::HashMap;
+
+use crate::utils::{binary_to_decimal, convert_char_bit_to_int};
+
+/// Trellis of a convolutional code with integer states, built once from `BitSM::states_map`.
+/// State number is the register read as a binary number, so the newest bit is the highest one.
+pub struct Trellis {
+    register_size: usize,
+    next_states: Vec<[usize; 2]>,
+    outputs: Vec<[Vec<u8>; 2]>,
+}
+
+impl Trellis {
+    pub fn from_states_map(states_map: &HashMap<String, HashMap<String, String>>) -> Self {
+        let states_count = states_map.len();
+        let register_size = states_count.ilog2() as usize;
+        let mut next_states = vec![[0, 0]; states_count];
+        let mut outputs = vec![[Vec::new(), Vec::new()]; states_count];
+
+        for (state, transitions) in states_map {
+            let state_index = binary_to_decimal(state);
+            for (next_state, output_bits) in transitions {
+                let input_bit = convert_char_bit_to_int(next_state.as_bytes()[0] as char);
+                next_states[state_index][input_bit] = binary_to_decimal(next_state);
+                outputs[state_index][input_bit] = output_bits
+                    .chars()
+                    .map(|bit| convert_char_bit_to_int(bit) as u8)
+                    .collect();
+            }
+        }
+
+        Trellis { register_size, next_states, outputs }
+    }
+
+    pub fn register_size(&self) -> usize { self.register_size }
+
+    pub fn states_count(&self) -> usize { self.next_states.len() }
+
+    /// Number of output bits per input bit (n of a rate 1/n code)
+    pub fn output_size(&self) -> usize { self.outputs[0][0].len() }
+
+    pub fn next_state(&self, state: usize, input_bit: usize) -> usize {
+        self.next_states[state][input_bit]
+    }
+
+    pub fn output(&self, state: usize, input_bit: usize) -> &[u8] {
+        &self.outputs[state][input_bit]
+    }
+
+    /// Input bit which moved the register into `state`
+    pub fn input_bit(&self, state: usize) -> u8 {
+        (state >> (self.register_size - 1)) as u8
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame_fix_algos::state_machine::BitSM;
+
+    #[test]
+    fn trellis_built_from_states_map() {
+        let states_map = BitSM::new().states_map();
+        let trellis = Trellis::from_states_map(&states_map);
+
+        assert_eq!(trellis.states_count(), 64);
+        assert_eq!(trellis.register_size(), 6);
+        assert_eq!(trellis.output_size(), 2);
+        assert_eq!(trellis.next_state(0, 1), 0b100000);
+        assert_eq!(trellis.next_state(0b100001, 0), 0b010000);
+        assert_eq!(trellis.output(0, 0), &[0, 0]);
+        assert_eq!(trellis.output(0, 1), &[1, 1]);
+        assert_eq!(trellis.input_bit(0b100000), 1);
+    }
+}
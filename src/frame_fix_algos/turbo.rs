@@ -0,0 +1,364 @@
+// Турбокод: два рекурсивных систематических свёрточных кодера (RSC), второй
+// кодирует перемеженные данные. RSC с обратной связью задаётся, как и BitSM,
+// восьмеричными полиномами: старший бит - вход регистра, дальше ячейки от самой
+// новой к самой старой. Номер состояния - регистр как двоичное число, самая
+// новая ячейка в старшем бите (как в Trellis).
+// Каждый кодер закрывается отдельно: K-1 шагов на вход подаётся бит обратной
+// связи, чтобы регистр обнулился. Порядок бит кадра: для каждого бита данных
+// x, z, z' (систематический и два проверочных), затем пары x, z хвоста первого
+// кодера и x', z' хвоста второго.
+// Декодер итеративный: два декодера BCJR в логарифмической области обмениваются
+// внешней информацией через перемежитель. Max-Log-MAP заменяет log(e^a + e^b)
+// на max(a, b), Log-MAP добавляет поправку log(1 + e^-|a-b|).
+// LLR положительны для 0, как в декодере Витерби.
+
+use crate::frame_fix_algos::interleaver::QppInterleaver;
+use crate::frame_fix_algos::viterbi::SoftSymbols;
+use crate::utils::{bits_from_str, bits_to_string};
+
+
+/// Recursive systematic convolutional code of rate 1/2
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RscCode {
+    constraint_length: usize,
+    feedback: u32,
+    feedforward: u32,
+}
+
+impl RscCode {
+    pub fn new(constraint_length: usize, feedback: u32, feedforward: u32) -> Self {
+        assert!(constraint_length >= 2, "Constraint length must be at least 2");
+        for generator in [feedback, feedforward] {
+            assert!(
+                generator < (1 << constraint_length) && generator >> (constraint_length - 1) == 1,
+                "Generator {generator:o} must have exactly {constraint_length} taps with the input one"
+            );
+        }
+        RscCode { constraint_length, feedback, feedforward }
+    }
+
+    /// LTE constituent code: feedback 13, feedforward 15
+    pub fn lte() -> Self { Self::new(4, 0o13, 0o15) }
+
+    pub fn register_size(&self) -> usize { self.constraint_length - 1 }
+
+    pub fn states_count(&self) -> usize { 1 << self.register_size() }
+
+    /// Register input for the data bit `input` in `state`
+    fn register_input(&self, state: usize, input: u8) -> u8 {
+        input ^ ((state as u32 & self.feedback).count_ones() % 2) as u8
+    }
+
+    /// Data bit which feeds a zero into the register, used for termination
+    fn tail_input(&self, state: usize) -> u8 {
+        self.register_input(state, 0)
+    }
+
+    /// Next state and parity bit of one step
+    fn step(&self, state: usize, input: u8) -> (usize, u8) {
+        let word = ((self.register_input(state, input) as usize) << self.register_size()) | state;
+        let parity = ((word as u32 & self.feedforward).count_ones() % 2) as u8;
+        (word >> 1, parity)
+    }
+
+    /// Parity bits of the data, followed by the tail as (systematic, parity) pairs
+    fn encode_terminated(&self, data: &[u8]) -> (Vec<u8>, Vec<(u8, u8)>) {
+        let mut state = 0;
+        let parity = data.iter().map(|&bit| {
+            let (next_state, parity) = self.step(state, bit);
+            state = next_state;
+            parity
+        }).collect();
+        let tail = (0..self.register_size()).map(|_| {
+            let input = self.tail_input(state);
+            let (next_state, parity) = self.step(state, input);
+            state = next_state;
+            (input, parity)
+        }).collect();
+        (parity, tail)
+    }
+
+    /// Parity bits of an unterminated frame
+    pub fn encode(&self, frame: &str) -> String {
+        let (parity, _) = self.encode_terminated(&bits_from_str(frame));
+        bits_to_string(&parity)
+    }
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TurboAlgorithm {
+    MaxLogMap,
+    LogMap,
+}
+
+impl TurboAlgorithm {
+    /// log(e^first + e^second) or its approximation
+    fn max_star(&self, first: f64, second: f64) -> f64 {
+        let maximum = first.max(second);
+        if maximum == f64::NEG_INFINITY {
+            return maximum;
+        }
+        match self {
+            TurboAlgorithm::MaxLogMap => maximum,
+            TurboAlgorithm::LogMap => maximum + (-(first - second).abs()).exp().ln_1p(),
+        }
+    }
+}
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TurboDecodeResult {
+    pub data: String,
+    /// A posteriori LLRs of the data bits after the last iteration
+    pub llrs: Vec<f64>,
+    pub iterations: usize,
+}
+
+
+pub struct TurboCode {
+    constituent: RscCode,
+    interleaver: QppInterleaver,
+}
+
+impl TurboCode {
+    pub fn new(constituent: RscCode, interleaver: QppInterleaver) -> Self {
+        TurboCode { constituent, interleaver }
+    }
+
+    /// LTE turbo code for a block of `len` bits with its QPP coefficients
+    pub fn lte(len: usize, f1: usize, f2: usize) -> Self {
+        Self::new(RscCode::lte(), QppInterleaver::new(len, f1, f2))
+    }
+
+    pub fn data_bits(&self) -> usize { self.interleaver.len() }
+
+    /// 3 bits per data bit and two terminated tails
+    pub fn codeword_bits(&self) -> usize {
+        3 * self.data_bits() + 4 * self.constituent.register_size()
+    }
+
+    pub fn encode(&self, frame: &str) -> String {
+        assert_eq!(frame.len(), self.data_bits(), "Turbo code encodes blocks of {} bits", self.data_bits());
+        let data = bits_from_str(frame);
+        let (parity, tail) = self.constituent.encode_terminated(&data);
+        let (interleaved_parity, interleaved_tail) = self.constituent.encode_terminated(&self.interleaver.interleave(&data));
+
+        let mut codeword = Vec::with_capacity(self.codeword_bits());
+        for index in 0..data.len() {
+            codeword.extend([data[index], parity[index], interleaved_parity[index]]);
+        }
+        for (systematic, parity) in tail.into_iter().chain(interleaved_tail) {
+            codeword.extend([systematic, parity]);
+        }
+        bits_to_string(&codeword)
+    }
+
+    pub fn decode(&self, symbols: &SoftSymbols, algorithm: TurboAlgorithm, iterations: usize) -> TurboDecodeResult {
+        let llrs = symbols.to_llrs();
+        assert_eq!(llrs.len(), self.codeword_bits(), "Turbo codeword must have {} symbols", self.codeword_bits());
+        let data_bits = self.data_bits();
+        let tail_len = self.constituent.register_size();
+
+        let systematic = llrs.iter().step_by(3).take(data_bits).copied().collect::<Vec<f64>>();
+        let parity = llrs.iter().skip(1).step_by(3).take(data_bits).copied().collect::<Vec<f64>>();
+        let interleaved_parity = llrs.iter().skip(2).step_by(3).take(data_bits).copied().collect::<Vec<f64>>();
+        let tails = &llrs[3 * data_bits..];
+        let tail_pairs = |tail: &[f64]| tail.chunks(2).map(|pair| (pair[0], pair[1])).collect::<Vec<(f64, f64)>>();
+        let (tail, interleaved_tail) = (tail_pairs(&tails[..2 * tail_len]), tail_pairs(&tails[2 * tail_len..]));
+        let interleaved_systematic = self.interleaver.interleave(&systematic);
+
+        let mut apriori = vec![0.0; data_bits];
+        let mut posterior = systematic.clone();
+        for _ in 0..iterations {
+            let first = self.constituent_decode(&systematic, &parity, &tail, &apriori, algorithm);
+            let first_extrinsic = extrinsic(&first, &systematic, &apriori);
+
+            let interleaved_apriori = self.interleaver.interleave(&first_extrinsic);
+            let second = self.constituent_decode(
+                &interleaved_systematic,
+                &interleaved_parity,
+                &interleaved_tail,
+                &interleaved_apriori,
+                algorithm
+            );
+            let interleaved_extrinsic = extrinsic(&second, &interleaved_systematic, &interleaved_apriori);
+            apriori = self.interleaver.deinterleave(&interleaved_extrinsic);
+            posterior = self.interleaver.deinterleave(&second);
+        }
+
+        let data = posterior.iter().map(|&llr| if llr < 0.0 { 1 } else { 0 }).collect::<Vec<u8>>();
+        TurboDecodeResult { data: bits_to_string(&data), llrs: posterior, iterations }
+    }
+
+    /// BCJR over the terminated trellis of one constituent code, a posteriori LLRs of the data bits
+    fn constituent_decode(
+        &self,
+        systematic: &[f64],
+        parity: &[f64],
+        tail: &[(f64, f64)],
+        apriori: &[f64],
+        algorithm: TurboAlgorithm
+    ) -> Vec<f64> {
+        let code = &self.constituent;
+        let states_count = code.states_count();
+        let steps = systematic.len() + tail.len();
+        let step_llrs = |step: usize| if step < systematic.len() {
+            (systematic[step] + apriori[step], parity[step], false)
+        } else {
+            let (systematic, parity) = tail[step - systematic.len()];
+            (systematic, parity, true)
+        };
+        // Метрика ветви: LLR / 2 со знаком + для бита 0 и - для бита 1
+        let branches = |state: usize, step: usize| {
+            let (systematic_llr, parity_llr, is_tail) = step_llrs(step);
+            let inputs = if is_tail { vec![code.tail_input(state)] } else { vec![0, 1] };
+            inputs.into_iter().map(move |input| {
+                let (next_state, parity) = code.step(state, input);
+                let metric = bit_metric(input, systematic_llr) + bit_metric(parity, parity_llr);
+                (input, next_state, metric)
+            })
+        };
+
+        let mut alphas = vec![vec![f64::NEG_INFINITY; states_count]; steps + 1];
+        alphas[0][0] = 0.0;
+        for step in 0..steps {
+            for state in 0..states_count {
+                if alphas[step][state] == f64::NEG_INFINITY {
+                    continue;
+                }
+                for (_, next_state, metric) in branches(state, step) {
+                    let candidate = alphas[step][state] + metric;
+                    alphas[step + 1][next_state] = algorithm.max_star(alphas[step + 1][next_state], candidate);
+                }
+            }
+        }
+
+        let mut betas = vec![vec![f64::NEG_INFINITY; states_count]; steps + 1];
+        betas[steps][0] = 0.0;
+        for step in (0..steps).rev() {
+            for state in 0..states_count {
+                betas[step][state] = branches(state, step).fold(f64::NEG_INFINITY, |beta, (_, next_state, metric)| {
+                    algorithm.max_star(beta, metric + betas[step + 1][next_state])
+                });
+            }
+        }
+
+        (0..systematic.len()).map(|step| {
+            let mut likelihoods = [f64::NEG_INFINITY; 2];
+            for (state, &alpha) in alphas[step].iter().enumerate() {
+                for (input, next_state, metric) in branches(state, step) {
+                    let path = alpha + metric + betas[step + 1][next_state];
+                    likelihoods[input as usize] = algorithm.max_star(likelihoods[input as usize], path);
+                }
+            }
+            likelihoods[0] - likelihoods[1]
+        }).collect()
+    }
+}
+
+
+fn bit_metric(bit: u8, llr: f64) -> f64 {
+    if bit == 0 { llr / 2.0 } else { -llr / 2.0 }
+}
+
+/// Part of the a posteriori LLRs not coming from the channel systematic bits or the a priori input
+fn extrinsic(posterior: &[f64], systematic: &[f64], apriori: &[f64]) -> Vec<f64> {
+    posterior.iter()
+        .zip(systematic)
+        .zip(apriori)
+        .map(|((&posterior, &systematic), &apriori)| posterior - systematic - apriori)
+        .collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame_fix_algos::prbs::{PrbsGenerator, PrbsPattern};
+    use crate::test_utils::bpsk_llrs;
+
+    /// BPSK LLRs with weak sign errors at every `error_step`-th symbol
+    fn noisy_llrs(codeword: &str, error_step: usize) -> Vec<f64> {
+        bpsk_llrs(codeword, |index| {
+            let magnitude = 1.0 + (index * 29 % 7) as f64 / 4.0;
+            if index % error_step == error_step / 2 { -0.5 * magnitude } else { magnitude }
+        })
+    }
+
+    #[test]
+    fn lte_constituent_impulse_response() {
+        let code = RscCode::lte();
+        // Рекурсивный код: отклик на одиночную единицу бесконечен с периодом 7
+        assert_eq!(code.encode("10000000000000"), "11110010111001");
+    }
+
+    #[test]
+    fn constituent_terminated_to_zero_state() {
+        let code = RscCode::lte();
+        let data = bits_from_str("1101001110");
+        let (parity, tail) = code.encode_terminated(&data);
+        assert_eq!(parity.len(), 10);
+        assert_eq!(tail.len(), 3);
+
+        let mut state = 0;
+        for &bit in data.iter().chain(tail.iter().map(|(input, _)| input)) {
+            state = code.step(state, bit).0;
+        }
+        assert_eq!(state, 0);
+    }
+
+    #[test]
+    fn turbo_codeword_layout() {
+        let turbo = TurboCode::lte(40, 3, 10);
+        let data = PrbsGenerator::new(PrbsPattern::Prbs7).generate(40);
+        let codeword = turbo.encode(&data);
+        assert_eq!(codeword.len(), 132);
+        assert_eq!(codeword.chars().step_by(3).take(40).collect::<String>(), data);
+        let parity = codeword.chars().skip(1).step_by(3).take(40).collect::<String>();
+        assert_eq!(parity, RscCode::lte().encode(&data));
+    }
+
+    #[test]
+    fn clean_frame_decoded() {
+        let turbo = TurboCode::lte(40, 3, 10);
+        let data = PrbsGenerator::new(PrbsPattern::Prbs9).generate(40);
+        let llrs = bpsk_llrs(&turbo.encode(&data), |_| 2.0);
+        let result = turbo.decode(&SoftSymbols::Llr(llrs), TurboAlgorithm::MaxLogMap, 1);
+        assert_eq!(result.data, data);
+        assert_eq!(result.iterations, 1);
+    }
+
+    #[test]
+    fn errors_corrected_by_both_algorithms() {
+        let turbo = TurboCode::lte(64, 7, 16);
+        let mut generator = PrbsGenerator::new(PrbsPattern::Prbs15);
+        generator.generate(100);
+        let data = generator.generate(64);
+        let symbols = SoftSymbols::Llr(noisy_llrs(&turbo.encode(&data), 8));
+
+        for algorithm in [TurboAlgorithm::MaxLogMap, TurboAlgorithm::LogMap] {
+            let result = turbo.decode(&symbols, algorithm, 8);
+            assert_eq!(result.data, data);
+        }
+    }
+
+    #[test]
+    fn iterations_increase_confidence() {
+        let turbo = TurboCode::lte(40, 3, 10);
+        let data = PrbsGenerator::new(PrbsPattern::Prbs23).generate(40);
+        let symbols = SoftSymbols::Llr(noisy_llrs(&turbo.encode(&data), 7));
+
+        let confidence = |result: &TurboDecodeResult| result.llrs.iter().map(|llr| llr.abs()).fold(f64::INFINITY, f64::min);
+        let first = turbo.decode(&symbols, TurboAlgorithm::LogMap, 1);
+        let last = turbo.decode(&symbols, TurboAlgorithm::LogMap, 6);
+        assert_eq!(last.data, data);
+        assert!(confidence(&last) > confidence(&first));
+    }
+
+    #[test]
+    #[should_panic(expected = "Generator 5 must have exactly 4 taps with the input one")]
+    fn feedback_generator_needs_input_tap() {
+        RscCode::new(4, 0o5, 0o15);
+    }
+}
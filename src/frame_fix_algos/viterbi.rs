@@ -0,0 +1,384 @@
+// Декодер Витерби: на каждом шаге для всех состояний решётки выполняется
+// сложение-сравнение-выбор (ACS), для каждого состояния хранится выжившее
+// предшествующее состояние, а в конце путь восстанавливается обратным проходом
+// (traceback) от состояния с наименьшей метрикой.
+// Жёсткое решение считает метрику ветви как расстояние Хэмминга, мягкое - по
+// LLR принятых символов (евклидова или корреляционная метрика).
+// Для кадров, закрытых нулевым хвостом, путь обязан закончиться в нулевом
+// состоянии; для tail-biting начальное состояние неизвестно и совпадает с
+// конечным, поэтому декодер проходит кадр по кругу (circular Viterbi): к кадру
+// приклеиваются его конец спереди и начало сзади, а результат берётся из середины.
+// Потоковый декодер хранит только последние traceback_depth столбцов выживших
+// путей и выдаёт каждый бит с фиксированной задержкой.
+
+use std::collections::VecDeque;
+
+use crate::frame_fix_algos::trellis::Trellis;
+use crate::utils::{bits_to_string, convert_char_bit_to_int};
+
+// Во сколько длин регистра кадр продлевается с каждой стороны при tail-biting декодировании
+const TAIL_BITING_WRAP_FACTOR: usize = 6;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Termination {
+    /// Encoder is flushed with K-1 zeros and ends in the zero state
+    ZeroTail,
+    /// Encoder register is preloaded with the last K-1 bits, so it ends where it started
+    TailBiting,
+    /// Encoder is stopped after the last data bit in an unknown state
+    Truncated,
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BranchMetric {
+    /// Squared distance between the received value and the BPSK point (+1 for 0, -1 for 1)
+    Euclidean,
+    /// Negated correlation between the received value and the BPSK point
+    Correlation,
+}
+
+/// Soft channel symbols. LLRs are positive for a likely 0, quantized values
+/// are offset-binary: 0 is the most confident 0 and 2^bits - 1 the most confident 1.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SoftSymbols {
+    Quantized { bits: u8, values: Vec<u8> },
+    Llr(Vec<f64>),
+}
+
+impl SoftSymbols {
+    pub fn to_llrs(&self) -> Vec<f64> {
+        match self {
+            SoftSymbols::Quantized { bits, values } => {
+                assert!((1..=8).contains(bits), "Quantization must be from 1 to 8 bits");
+                let max_value = ((1u16 << bits) - 1) as f64;
+                let center = max_value / 2.0;
+                values.iter().map(|&value| {
+                    assert!(value as f64 <= max_value, "Value {value} does not fit in {bits} bits");
+                    (center - value as f64) / center
+                }).collect()
+            }
+            SoftSymbols::Llr(llrs) => llrs.clone(),
+        }
+    }
+}
+
+
+fn hamming_distance(first: &[u8], second: &[u8]) -> f64 {
+    first.iter().zip(second).filter(|(a, b)| a != b).count() as f64
+}
+
+fn soft_distance(expected: &[u8], received: &[f64], metric: BranchMetric) -> f64 {
+    expected.iter().zip(received).map(|(&bit, &value)| {
+        let point = if bit == 0 { 1.0 } else { -1.0 };
+        match metric {
+            BranchMetric::Euclidean => (value - point) * (value - point),
+            BranchMetric::Correlation => -value * point,
+        }
+    }).sum()
+}
+
+fn decode_with_metric<T, F>(
+    trellis: &Trellis,
+    symbols: &[T],
+    start_state: Option<usize>,
+    end_state: Option<usize>,
+    branch_metric: &F
+) -> Vec<u8>
+    where F: Fn(&[u8], &[T]) -> f64
+{
+    let states_count = trellis.states_count();
+    let mut path_metrics = match start_state {
+        Some(state) => {
+            let mut metrics = vec![f64::INFINITY; states_count];
+            metrics[state] = 0.0;
+            metrics
+        }
+        None => vec![0.0; states_count],
+    };
+    let mut survivors: Vec<Vec<usize>> = Vec::with_capacity(symbols.len() / trellis.output_size());
+
+    for received in symbols.chunks_exact(trellis.output_size()) {
+        let (next_metrics, predecessors) = add_compare_select(trellis, &path_metrics, received, branch_metric);
+        survivors.push(predecessors);
+        path_metrics = next_metrics;
+    }
+
+    let best_state = end_state.unwrap_or_else(|| best_state(&path_metrics));
+    traceback(trellis, &survivors, best_state)
+}
+
+/// One trellis step: new path metrics and the surviving predecessor of every state
+fn add_compare_select<T, F>(
+    trellis: &Trellis,
+    path_metrics: &[f64],
+    received: &[T],
+    branch_metric: &F
+) -> (Vec<f64>, Vec<usize>)
+    where F: Fn(&[u8], &[T]) -> f64
+{
+    let states_count = trellis.states_count();
+    let mut next_metrics = vec![f64::INFINITY; states_count];
+    let mut predecessors = vec![0; states_count];
+    for (state, &metric) in path_metrics.iter().enumerate() {
+        if metric == f64::INFINITY {
+            continue;
+        }
+        for input_bit in 0..2 {
+            let next_state = trellis.next_state(state, input_bit);
+            let candidate = metric + branch_metric(trellis.output(state, input_bit), received);
+            if candidate < next_metrics[next_state] {
+                next_metrics[next_state] = candidate;
+                predecessors[next_state] = state;
+            }
+        }
+    }
+    (next_metrics, predecessors)
+}
+
+fn best_state(path_metrics: &[f64]) -> usize {
+    (0..path_metrics.len())
+        .min_by(|&first, &second| path_metrics[first].total_cmp(&path_metrics[second]))
+        .unwrap_or(0)
+}
+
+fn decode_terminated_with_metric<T, F>(
+    trellis: &Trellis,
+    symbols: &[T],
+    termination: Termination,
+    branch_metric: &F
+) -> Vec<u8>
+    where T: Clone, F: Fn(&[u8], &[T]) -> f64
+{
+    match termination {
+        Termination::Truncated => decode_with_metric(trellis, symbols, Some(0), None, branch_metric),
+        Termination::ZeroTail => {
+            let mut decoded = decode_with_metric(trellis, symbols, Some(0), Some(0), branch_metric);
+            decoded.truncate(decoded.len().saturating_sub(trellis.register_size()));
+            decoded
+        }
+        Termination::TailBiting => {
+            let output_size = trellis.output_size();
+            let steps = symbols.len() / output_size;
+            let wrap = steps.min(TAIL_BITING_WRAP_FACTOR * trellis.register_size());
+            let mut wrapped_symbols = symbols[(steps - wrap) * output_size..steps * output_size].to_vec();
+            wrapped_symbols.extend_from_slice(&symbols[..steps * output_size]);
+            wrapped_symbols.extend_from_slice(&symbols[..wrap * output_size]);
+
+            let decoded = decode_with_metric(trellis, &wrapped_symbols, None, None, branch_metric);
+            decoded[wrap..wrap + steps].to_vec()
+        }
+    }
+}
+
+/// Decode hard channel bits (0/1) assuming the encoder started in the zero state
+pub fn decode_hard(trellis: &Trellis, symbols: &[u8]) -> Vec<u8> {
+    decode_hard_terminated(trellis, symbols, Termination::Truncated)
+}
+
+pub fn decode_hard_terminated(trellis: &Trellis, symbols: &[u8], termination: Termination) -> Vec<u8> {
+    decode_terminated_with_metric(trellis, symbols, termination, &hamming_distance)
+}
+
+/// Decode soft channel symbols assuming the encoder started in the zero state
+pub fn decode_soft(trellis: &Trellis, symbols: &SoftSymbols, metric: BranchMetric) -> Vec<u8> {
+    decode_soft_terminated(trellis, symbols, metric, Termination::Truncated)
+}
+
+pub fn decode_soft_terminated(
+    trellis: &Trellis,
+    symbols: &SoftSymbols,
+    metric: BranchMetric,
+    termination: Termination
+) -> Vec<u8> {
+    let llrs = symbols.to_llrs();
+    decode_terminated_with_metric(
+        trellis,
+        &llrs,
+        termination,
+        &|expected: &[u8], received: &[f64]| soft_distance(expected, received, metric)
+    )
+}
+
+fn traceback<'a, I>(trellis: &Trellis, survivors: I, end_state: usize) -> Vec<u8>
+    where I: IntoIterator<Item = &'a Vec<usize>>, I::IntoIter: DoubleEndedIterator
+{
+    let mut decoded = Vec::new();
+    let mut state = end_state;
+    for predecessors in survivors.into_iter().rev() {
+        decoded.push(trellis.input_bit(state));
+        state = predecessors[state];
+    }
+    decoded.reverse();
+    decoded
+}
+
+
+/// Viterbi decoder for an unbounded stream of symbols. Every bit is emitted
+/// `traceback_depth` steps after its symbols arrive, and only that many survivor
+/// columns are kept, so memory does not grow with the stream length.
+pub struct StreamingViterbi {
+    trellis: Trellis,
+    traceback_depth: usize,
+    metric: BranchMetric,
+    path_metrics: Vec<f64>,
+    survivors: VecDeque<Vec<usize>>,
+    pending_symbols: Vec<f64>,
+}
+
+impl StreamingViterbi {
+    /// Decoder for a stream starting in the zero state.
+    /// A traceback depth of about five constraint lengths is usually enough.
+    pub fn new(trellis: Trellis, traceback_depth: usize, metric: BranchMetric) -> Self {
+        assert!(traceback_depth > 0, "Traceback depth must be positive");
+        let mut path_metrics = vec![f64::INFINITY; trellis.states_count()];
+        path_metrics[0] = 0.0;
+        StreamingViterbi {
+            trellis,
+            traceback_depth,
+            metric,
+            path_metrics,
+            survivors: VecDeque::with_capacity(traceback_depth + 1),
+            pending_symbols: Vec::new(),
+        }
+    }
+
+    /// Push received hard bits, returning the bits decoded so far
+    pub fn push(&mut self, frame: &str) -> String {
+        let llrs = frame.chars()
+            .map(|bit| if convert_char_bit_to_int(bit) == 0 { 1.0 } else { -1.0 })
+            .collect::<Vec<f64>>();
+        self.push_soft(&SoftSymbols::Llr(llrs))
+    }
+
+    /// Push received soft symbols, returning the bits decoded so far
+    pub fn push_soft(&mut self, symbols: &SoftSymbols) -> String {
+        self.pending_symbols.extend(symbols.to_llrs());
+        let output_size = self.trellis.output_size();
+        let steps = self.pending_symbols.len() / output_size;
+        let metric = self.metric;
+        let branch_metric = |expected: &[u8], received: &[f64]| soft_distance(expected, received, metric);
+
+        let mut decoded = Vec::new();
+        for step in 0..steps {
+            let received = &self.pending_symbols[step * output_size..(step + 1) * output_size];
+            let (mut next_metrics, predecessors) =
+                add_compare_select(&self.trellis, &self.path_metrics, received, &branch_metric);
+            // Нормировка, чтобы метрики не росли неограниченно на длинном потоке
+            let min_metric = next_metrics[best_state(&next_metrics)];
+            next_metrics.iter_mut().for_each(|metric| *metric -= min_metric);
+            self.path_metrics = next_metrics;
+            self.survivors.push_back(predecessors);
+
+            if self.survivors.len() > self.traceback_depth {
+                let path = traceback(&self.trellis, &self.survivors, best_state(&self.path_metrics));
+                decoded.push(path[0]);
+                self.survivors.pop_front();
+            }
+        }
+        self.pending_symbols.drain(..steps * output_size);
+        bits_to_string(&decoded)
+    }
+
+    /// Emit the bits still held in the traceback window and start a new stream
+    pub fn flush(&mut self) -> String {
+        let decoded = traceback(&self.trellis, &self.survivors, best_state(&self.path_metrics));
+        self.survivors.clear();
+        self.pending_symbols.clear();
+        self.path_metrics.iter_mut().for_each(|metric| *metric = f64::INFINITY);
+        self.path_metrics[0] = 0.0;
+        bits_to_string(&decoded)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame_fix_algos::state_machine::BitSM;
+
+    #[test]
+    fn zero_sequence_decoded() {
+        let trellis = Trellis::from_states_map(&BitSM::new().states_map());
+        assert_eq!(decode_hard(&trellis, &[0; 20]), vec![0; 10]);
+    }
+
+    #[test]
+    fn odd_trailing_symbol_ignored() {
+        let trellis = Trellis::from_states_map(&BitSM::new().states_map());
+        assert_eq!(decode_hard(&trellis, &[1, 1, 1]), vec![1]);
+    }
+
+    #[test]
+    fn quantized_symbols_mapped_to_llrs() {
+        let symbols = SoftSymbols::Quantized { bits: 3, values: vec![0, 7, 3, 4] };
+        let llrs = symbols.to_llrs();
+        assert_eq!(llrs[0], 1.0);
+        assert_eq!(llrs[1], -1.0);
+        assert!(llrs[2] > 0.0 && llrs[2] < 0.2);
+        assert!(llrs[3] < 0.0 && llrs[3] > -0.2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Value 16 does not fit in 4 bits")]
+    fn quantized_value_out_of_range() {
+        SoftSymbols::Quantized { bits: 4, values: vec![16] }.to_llrs();
+    }
+
+    #[test]
+    fn correlation_and_euclidean_metrics_agree() {
+        let trellis = Trellis::from_states_map(&BitSM::new().states_map());
+        let symbols = SoftSymbols::Llr(vec![-0.9, -1.1, 0.2, -0.8, 0.1, 0.4, -0.7, 1.2, 0.9, 1.0, 1.1, 0.8]);
+        assert_eq!(
+            decode_soft(&trellis, &symbols, BranchMetric::Euclidean),
+            decode_soft(&trellis, &symbols, BranchMetric::Correlation)
+        );
+    }
+
+    fn encoded_prbs_stream(frame_len: usize) -> (String, String) {
+        use crate::frame_fix_algos::nasa_convo_code::encode;
+        use crate::frame_fix_algos::prbs::{PrbsGenerator, PrbsPattern};
+        let frame = PrbsGenerator::new(PrbsPattern::Prbs9).generate(frame_len);
+        let mut bit_sm = BitSM::new();
+        let states_map = bit_sm.states_map();
+        let encoded_frame = encode(frame.clone(), &mut bit_sm, &states_map);
+        (frame, encoded_frame)
+    }
+
+    #[test]
+    fn stream_decoded_with_fixed_latency() {
+        let (frame, encoded_frame) = encoded_prbs_stream(400);
+        let mut decoder = StreamingViterbi::new(Trellis::from_states_map(&BitSM::new().states_map()), 35, BranchMetric::Correlation);
+
+        assert_eq!(decoder.push(&encoded_frame[..69]), "");
+        let decoded = decoder.push(&encoded_frame[69..73]);
+        assert_eq!(decoded, &frame[..1]);
+        assert!(decoder.survivors.len() <= 35);
+    }
+
+    #[test]
+    fn stream_decoded_in_chunks_with_errors() {
+        let (frame, encoded_frame) = encoded_prbs_stream(2000);
+        let mut corrupted_frame = encoded_frame.into_bytes();
+        for index in (17..corrupted_frame.len()).step_by(97) {
+            corrupted_frame[index] = if corrupted_frame[index] == b'1' { b'0' } else { b'1' };
+        }
+        let corrupted_frame = String::from_utf8(corrupted_frame).unwrap();
+
+        let mut decoder = StreamingViterbi::new(Trellis::from_states_map(&BitSM::new().states_map()), 42, BranchMetric::Euclidean);
+        let mut decoded = String::new();
+        let mut start = 0;
+        for chunk_len in [1, 7, 130, 33].iter().cycle() {
+            if start >= corrupted_frame.len() {
+                break;
+            }
+            let end = (start + chunk_len).min(corrupted_frame.len());
+            decoded.push_str(&decoder.push(&corrupted_frame[start..end]));
+            assert!(decoder.survivors.len() <= 42);
+            start = end;
+        }
+        decoded.push_str(&decoder.flush());
+        assert_eq!(decoded, frame);
+    }
+}
@@ -0,0 +1,375 @@
+// Арифметика конечных полей GF(2^m) и многочленов над ними.
+// Элементы поля - числа 0..2^m-1, где бит i - коэффициент при a^i в полиномиальном
+// базисе. Умножение и деление идут через таблицы степеней (exp) и логарифмов (log)
+// примитивного элемента a, корня примитивного полинома поля.
+// GF(2) - частный случай m = 1 с полиномом x + 1, поэтому многочлены над GF(2)
+// (CRC, порождающие полиномы циклических кодов) используют тот же тип.
+
+// Примитивные полиномы с наименьшим числом членов для m = 1..16
+const DEFAULT_PRIMITIVE_POLYNOMIALS: [u32; 16] = [
+    0b11, 0b111, 0b1011, 0b10011, 0b100101, 0b1000011, 0b10001001, 0x11d,
+    0x211, 0x409, 0x805, 0x1053, 0x201b, 0x4443, 0x8003, 0x1100b,
+];
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GaloisField {
+    m: u32,
+    primitive_polynomial: u32,
+    exp: Vec<u16>,
+    log: Vec<usize>,
+}
+
+impl GaloisField {
+    /// GF(2^m) built from a primitive polynomial including the x^m term, e.g. 0b10011 for GF(16)
+    pub fn new(m: u32, primitive_polynomial: u32) -> Self {
+        assert!((1..=16).contains(&m), "Only fields from GF(2) to GF(2^16) are supported");
+        assert!(primitive_polynomial >> m == 1, "Polynomial {primitive_polynomial:#b} is not of degree {m}");
+        assert!(primitive_polynomial & 1 == 1, "Polynomial {primitive_polynomial:#b} has no constant term");
+        let order = (1usize << m) - 1;
+        let mut exp = vec![0u16; 2 * order];
+        let mut log = vec![0usize; order + 1];
+        let mut value: u32 = 1;
+        for power in 0..order {
+            exp[power] = value as u16;
+            exp[power + order] = value as u16;
+            log[value as usize] = power;
+            value <<= 1;
+            if value >> m == 1 {
+                value ^= primitive_polynomial;
+            }
+            // a^(2^m - 1) = 1, и раньше a в единицу не возвращается
+            assert!(
+                value != 0 && (value == 1) == (power == order - 1),
+                "Polynomial {primitive_polynomial:#b} is not primitive"
+            );
+        }
+        GaloisField { m, primitive_polynomial, exp, log }
+    }
+
+    /// GF(2^m) with the conventional primitive polynomial, e.g. x^4 + x + 1 for GF(16)
+    pub fn with_default_polynomial(m: u32) -> Self {
+        assert!((1..=16).contains(&m), "Only fields from GF(2) to GF(2^16) are supported");
+        Self::new(m, DEFAULT_PRIMITIVE_POLYNOMIALS[m as usize - 1])
+    }
+
+    /// GF(2) with the only nonzero element 1
+    pub fn binary() -> Self { Self::new(1, 0b11) }
+
+    pub fn m(&self) -> u32 { self.m }
+
+    pub fn primitive_polynomial(&self) -> u32 { self.primitive_polynomial }
+
+    /// Number of elements, 2^m
+    pub fn size(&self) -> usize { 1 << self.m }
+
+    /// Order of the multiplicative group, 2^m - 1
+    pub fn order(&self) -> usize { self.size() - 1 }
+
+    pub fn add(&self, first: u16, second: u16) -> u16 { first ^ second }
+
+    pub fn multiply(&self, first: u16, second: u16) -> u16 {
+        if first == 0 || second == 0 {
+            return 0;
+        }
+        self.exp[self.log[first as usize] + self.log[second as usize]]
+    }
+
+    pub fn divide(&self, dividend: u16, divisor: u16) -> u16 {
+        assert!(divisor != 0, "Division by zero in GF(2^{})", self.m);
+        if dividend == 0 {
+            return 0;
+        }
+        self.exp[self.log[dividend as usize] + self.order() - self.log[divisor as usize]]
+    }
+
+    pub fn inverse(&self, value: u16) -> u16 { self.divide(1, value) }
+
+    /// a^power for any integer power
+    pub fn exp(&self, power: i64) -> u16 {
+        self.exp[power.rem_euclid(self.order() as i64) as usize]
+    }
+
+    /// Power of the primitive element equal to `value`
+    pub fn log(&self, value: u16) -> usize {
+        assert!(value != 0, "Logarithm of zero in GF(2^{})", self.m);
+        self.log[value as usize]
+    }
+
+    /// value^power for any integer power
+    pub fn power(&self, value: u16, power: i64) -> u16 {
+        if value == 0 {
+            assert!(power > 0, "Zero raised to a non-positive power");
+            return 0;
+        }
+        self.exp(self.log(value) as i64 * power)
+    }
+}
+
+
+/// Polynomial over GF(2^m) with coefficients from the lowest degree.
+/// Leading zero coefficients are always trimmed, the zero polynomial has no coefficients.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GfPolynomial {
+    coefficients: Vec<u16>,
+}
+
+impl GfPolynomial {
+    pub fn new(mut coefficients: Vec<u16>) -> Self {
+        while coefficients.last() == Some(&0) {
+            coefficients.pop();
+        }
+        GfPolynomial { coefficients }
+    }
+
+    pub fn zero() -> Self { GfPolynomial { coefficients: Vec::new() } }
+
+    pub fn one() -> Self { GfPolynomial { coefficients: vec![1] } }
+
+    /// c * x^degree
+    pub fn monomial(coefficient: u16, degree: usize) -> Self {
+        let mut coefficients = vec![0; degree + 1];
+        coefficients[degree] = coefficient;
+        Self::new(coefficients)
+    }
+
+    /// Polynomial over GF(2) from a binary string with the highest degree first, "1011" is x^3 + x + 1
+    pub fn from_bits(bits: &str) -> Self {
+        Self::new(bits.chars().rev().map(|bit| if bit == '1' { 1 } else { 0 }).collect())
+    }
+
+    /// Binary string with the highest degree first, padded with zeros to `len` bits
+    pub fn to_bits(&self, len: usize) -> String {
+        assert!(self.coefficients.len() <= len, "Polynomial does not fit in {len} bits");
+        assert!(self.coefficients.iter().all(|&coefficient| coefficient <= 1), "Polynomial is not over GF(2)");
+        (0..len).rev().map(|degree| if self.coefficient(degree) == 1 { '1' } else { '0' }).collect()
+    }
+
+    pub fn coefficients(&self) -> &[u16] { &self.coefficients }
+
+    pub fn coefficient(&self, degree: usize) -> u16 {
+        self.coefficients.get(degree).copied().unwrap_or(0)
+    }
+
+    /// Degree of the polynomial, None for the zero polynomial
+    pub fn degree(&self) -> Option<usize> { self.coefficients.len().checked_sub(1) }
+
+    pub fn is_zero(&self) -> bool { self.coefficients.is_empty() }
+
+    pub fn add(&self, other: &GfPolynomial) -> GfPolynomial {
+        let len = self.coefficients.len().max(other.coefficients.len());
+        Self::new((0..len).map(|degree| self.coefficient(degree) ^ other.coefficient(degree)).collect())
+    }
+
+    pub fn scale(&self, scalar: u16, field: &GaloisField) -> GfPolynomial {
+        Self::new(self.coefficients.iter().map(|&coefficient| field.multiply(coefficient, scalar)).collect())
+    }
+
+    /// Multiply by x^shift
+    pub fn shift(&self, shift: usize) -> GfPolynomial {
+        if self.is_zero() {
+            return Self::zero();
+        }
+        let mut coefficients = vec![0; shift];
+        coefficients.extend_from_slice(&self.coefficients);
+        GfPolynomial { coefficients }
+    }
+
+    pub fn multiply(&self, other: &GfPolynomial, field: &GaloisField) -> GfPolynomial {
+        if self.is_zero() || other.is_zero() {
+            return Self::zero();
+        }
+        let mut coefficients = vec![0; self.coefficients.len() + other.coefficients.len() - 1];
+        for (i, &first) in self.coefficients.iter().enumerate() {
+            for (j, &second) in other.coefficients.iter().enumerate() {
+                coefficients[i + j] ^= field.multiply(first, second);
+            }
+        }
+        Self::new(coefficients)
+    }
+
+    /// Quotient and remainder of the division by `divisor`
+    pub fn div_rem(&self, divisor: &GfPolynomial, field: &GaloisField) -> (GfPolynomial, GfPolynomial) {
+        let divisor_degree = divisor.degree().expect("Division by the zero polynomial");
+        let leading_inverse = field.inverse(divisor.coefficients[divisor_degree]);
+        let mut remainder = self.coefficients.clone();
+        let quotient_len = remainder.len().saturating_sub(divisor_degree);
+        let mut quotient = vec![0; quotient_len];
+
+        for degree in (divisor_degree..remainder.len()).rev() {
+            let factor = field.multiply(remainder[degree], leading_inverse);
+            if factor == 0 {
+                continue;
+            }
+            quotient[degree - divisor_degree] = factor;
+            for (index, &coefficient) in divisor.coefficients.iter().enumerate() {
+                remainder[degree - divisor_degree + index] ^= field.multiply(factor, coefficient);
+            }
+        }
+        remainder.truncate(divisor_degree);
+        (Self::new(quotient), Self::new(remainder))
+    }
+
+    /// Remainder of the division by x^degree
+    pub fn truncate(&self, degree: usize) -> GfPolynomial {
+        Self::new(self.coefficients.iter().take(degree).copied().collect())
+    }
+
+    /// Formal derivative, only odd powers survive in characteristic 2
+    pub fn derivative(&self) -> GfPolynomial {
+        Self::new(
+            self.coefficients.iter()
+                .enumerate()
+                .skip(1)
+                .map(|(degree, &coefficient)| if degree % 2 == 1 { coefficient } else { 0 })
+                .collect()
+        )
+    }
+
+    pub fn evaluate(&self, x: u16, field: &GaloisField) -> u16 {
+        self.coefficients.iter().rev().fold(0, |result, &coefficient| field.multiply(result, x) ^ coefficient)
+    }
+}
+
+
+/// Berlekamp-Massey algorithm: the shortest error locator Lambda(x) generating the
+/// syndromes S_1..S_2t (passed from index 0). Decoding with erasures starts from the
+/// erasure locator with `initial_length` equal to the number of erasures.
+pub fn berlekamp_massey(
+    syndromes: &[u16],
+    initial_locator: GfPolynomial,
+    initial_length: usize,
+    field: &GaloisField
+) -> GfPolynomial {
+    let mut locator = initial_locator;
+    let mut previous = locator.clone();
+    let mut length = initial_length;
+    for step in initial_length + 1..=syndromes.len() {
+        let discrepancy = (0..step).fold(0, |sum, index| {
+            sum ^ field.multiply(locator.coefficient(index), syndromes[step - 1 - index])
+        });
+
+        previous = previous.shift(1);
+        if discrepancy == 0 {
+            continue;
+        }
+        let next_locator = locator.add(&previous.scale(discrepancy, field));
+        if 2 * length < step + initial_length {
+            length = step + initial_length - length;
+            previous = locator.scale(field.inverse(discrepancy), field);
+        }
+        locator = next_locator;
+    }
+    locator
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gf16_tables() {
+        let field = GaloisField::new(4, 0b10011);
+        assert_eq!(field.order(), 15);
+        assert_eq!(field.exp(4), 0b0011);
+        assert_eq!(field.exp(15), 1);
+        assert_eq!(field.exp(-1), 0b1001);
+        assert_eq!(field.log(0b0011), 4);
+        assert_eq!(field.multiply(0b1000, 0b0010), 0b0011);
+        assert_eq!(field.power(0b0010, 5), field.exp(5));
+    }
+
+    #[test]
+    fn gf256_arithmetic() {
+        let field = GaloisField::new(8, 0x11d);
+        assert_eq!(field.multiply(0x80, 0x02), 0x1d);
+        assert_eq!(field.multiply(0x53, 0xca), field.multiply(0xca, 0x53));
+        for value in 1..=255 {
+            assert_eq!(field.multiply(value, field.inverse(value)), 1);
+            assert_eq!(field.divide(field.multiply(value, 0x57), 0x57), value);
+        }
+    }
+
+    #[test]
+    fn default_polynomials_are_primitive() {
+        for m in 1..=16 {
+            assert_eq!(GaloisField::with_default_polynomial(m).order(), (1 << m) - 1);
+        }
+    }
+
+    #[test]
+    fn locator_found_by_berlekamp_massey() {
+        let field = GaloisField::new(4, 0b10011);
+        // Ошибки в позициях a^3 и a^10: S_j = a^3j + a^10j
+        let syndromes = (1..=4)
+            .map(|j| field.exp(3 * j) ^ field.exp(10 * j))
+            .collect::<Vec<u16>>();
+        let locator = berlekamp_massey(&syndromes, GfPolynomial::one(), 0, &field);
+        let expected = GfPolynomial::new(vec![1, field.exp(3)])
+            .multiply(&GfPolynomial::new(vec![1, field.exp(10)]), &field);
+        assert_eq!(locator, expected);
+    }
+
+    #[test]
+    fn binary_field() {
+        let field = GaloisField::binary();
+        assert_eq!(field.size(), 2);
+        assert_eq!(field.multiply(1, 1), 1);
+        assert_eq!(field.inverse(1), 1);
+        assert_eq!(field.add(1, 1), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Polynomial 0b11111 is not primitive")]
+    fn non_primitive_polynomial_rejected() {
+        // x^4 + x^3 + x^2 + x + 1 неприводим, но корень имеет порядок 5
+        GaloisField::new(4, 0b11111);
+    }
+
+    #[test]
+    #[should_panic(expected = "Polynomial 0b11000 has no constant term")]
+    fn polynomial_without_constant_term_rejected() {
+        // x^4 + x^3 = x^3 (x + 1): степени a не возвращаются в единицу
+        GaloisField::new(4, 0b11000);
+    }
+
+    #[test]
+    fn binary_polynomials_divided() {
+        let field = GaloisField::binary();
+        let dividend = GfPolynomial::from_bits("1011");
+        let divisor = GfPolynomial::from_bits("11");
+        let (quotient, remainder) = dividend.div_rem(&divisor, &field);
+        assert_eq!(quotient.to_bits(3), "110");
+        assert_eq!(remainder, GfPolynomial::one());
+        assert_eq!(quotient.multiply(&divisor, &field).add(&remainder), dividend);
+    }
+
+    #[test]
+    fn polynomials_over_gf256() {
+        let field = GaloisField::new(8, 0x11d);
+        // (x + 1)(x + a) = x^2 + 3x + 2
+        let product = GfPolynomial::new(vec![1, 1]).multiply(&GfPolynomial::new(vec![2, 1]), &field);
+        assert_eq!(product.coefficients(), &[2, 3, 1]);
+        assert_eq!(product.evaluate(1, &field), 0);
+        assert_eq!(product.evaluate(2, &field), 0);
+        assert_eq!(product.derivative().coefficients(), &[3]);
+
+        let dividend = GfPolynomial::new(vec![7, 0, 200, 13, 99]);
+        let (quotient, remainder) = dividend.div_rem(&product, &field);
+        assert_eq!(remainder.degree(), Some(1));
+        assert_eq!(quotient.multiply(&product, &field).add(&remainder), dividend);
+    }
+
+    #[test]
+    fn polynomial_helpers() {
+        let polynomial = GfPolynomial::from_bits("00101");
+        assert_eq!(polynomial.degree(), Some(2));
+        assert_eq!(polynomial.to_bits(4), "0101");
+        assert_eq!(polynomial.shift(2).to_bits(5), "10100");
+        assert_eq!(polynomial.truncate(1), GfPolynomial::one());
+        assert_eq!(GfPolynomial::monomial(1, 3).to_bits(4), "1000");
+        assert!(GfPolynomial::new(vec![0, 0]).is_zero());
+        assert_eq!(GfPolynomial::zero().degree(), None);
+    }
+}
@@ -1,3 +1,7 @@
 pub mod frame_prepare_algos;
 pub mod frame_fix_algos;
 pub mod utils;
+pub mod galois;
+
+#[cfg(test)]
+mod test_utils;
@@ -0,0 +1,22 @@
+// Общие вспомогательные функции тестов
+
+use std::borrow::Borrow;
+
+/// Frame with the bits at `indexes` inverted
+pub fn flip_bits(frame: &str, indexes: impl IntoIterator<Item = impl Borrow<usize>>) -> String {
+    let mut bits = frame.as_bytes().to_vec();
+    for index in indexes {
+        let index = *index.borrow();
+        bits[index] = if bits[index] == b'1' { b'0' } else { b'1' };
+    }
+    String::from_utf8(bits).unwrap()
+}
+
+/// BPSK LLRs of a frame: `magnitude(index)` for a 0 bit and its negation for a 1 bit,
+/// so a negative magnitude models a symbol received on the wrong side
+pub fn bpsk_llrs(frame: &str, mut magnitude: impl FnMut(usize) -> f64) -> Vec<f64> {
+    frame.bytes().enumerate().map(|(index, bit)| {
+        let magnitude = magnitude(index);
+        if bit == b'0' { magnitude } else { -magnitude }
+    }).collect()
+}
@@ -19,6 +19,27 @@ pub fn binary_to_decimal(bits: &str) -> usize {
     result as usize
 }
 
+pub fn bits_from_str(bits: &str) -> Vec<u8> {
+    bits.chars().map(|bit| convert_char_bit_to_int(bit) as u8).collect()
+}
+
+pub fn bits_to_string(bits: &[u8]) -> String {
+    bits.iter().map(|&bit| if bit == 1 { '1' } else { '0' }).collect()
+}
+
+/// Bits of every byte, most significant bit first
+pub fn bytes_to_bits(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:08b}", byte)).collect()
+}
+
+pub fn bits_to_bytes(bits: &str) -> Vec<u8> {
+    assert_eq!(bits.len() % 8, 0, "Frame is not a whole number of bytes");
+    bits.as_bytes()
+        .chunks(8)
+        .map(|byte| byte.iter().fold(0, |result, &bit| (result << 1) | (bit - b'0')))
+        .collect()
+}
+
 
 #[cfg(test)]
 mod tests {
@@ -43,4 +64,12 @@ mod tests {
         let x: u32 = 259;
         assert_eq!(3, x.count_ones());
     }
+
+    #[test]
+    fn test_bits_conversion() {
+        assert_eq!(bits_from_str("1011"), vec![1, 0, 1, 1]);
+        assert_eq!(bits_to_string(&[0, 1, 1, 0]), "0110");
+        assert_eq!(bytes_to_bits(&[0xa5, 0x01]), "1010010100000001");
+        assert_eq!(bits_to_bytes("1010010100000001"), vec![0xa5, 0x01]);
+    }
 }
\ No newline at end of file